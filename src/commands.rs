@@ -1,5 +1,25 @@
+mod analyze;
 mod cli;
+mod connection;
+mod detection;
 mod generate;
+mod impact;
+mod lint;
+mod order;
+mod output;
+mod report;
+mod subset;
+mod validate;
 
+pub use analyze::*;
 pub use cli::*;
+pub use connection::*;
+pub use detection::*;
 pub use generate::*;
+pub use impact::*;
+pub use lint::*;
+pub use order::*;
+pub use output::*;
+pub use report::*;
+pub use subset::*;
+pub use validate::*;
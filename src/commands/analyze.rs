@@ -0,0 +1,103 @@
+use crate::commands::{write_output, DetectionArgs, ReportFormat};
+use crate::databases::Graph;
+use anyhow::Result;
+use clap::Args;
+use itertools::Itertools;
+use serde::Serialize;
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct AnalyzeArgs {
+    #[clap(flatten)]
+    detection: DetectionArgs,
+
+    /// Number of the most referenced tables and the longest chains to report
+    #[clap(long, default_value = "10")]
+    top: usize,
+
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: String,
+
+    /// Output file path, or `-` for stdout
+    #[clap(short, long, default_value = "-")]
+    output: String,
+}
+
+/// Insight into the graph of the relations
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Analysis {
+    orphan_tables: Vec<String>,
+    cycles: Vec<Vec<String>>,
+    most_referenced_tables: Vec<ReferencedTable>,
+    longest_chains: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ReferencedTable {
+    table: String,
+    references: usize,
+}
+
+pub async fn run_analyze(args: AnalyzeArgs) -> Result<()> {
+    let format = ReportFormat::parse(&args.format)?;
+    let detection = args.detection.resolve()?;
+    let tables = detection.tables().await?;
+    let relations = detection.relations(tables.clone())?;
+    let graph = Graph::new(&tables, &relations);
+
+    let analysis = Analysis {
+        orphan_tables: graph.orphans().into_iter().cloned().collect(),
+        cycles: graph.cycles(),
+        most_referenced_tables: graph
+            .nodes
+            .iter()
+            .map(|name| ReferencedTable {
+                table: name.clone(),
+                references: graph.in_degree(name),
+            })
+            .filter(|t| t.references > 0)
+            .sorted_by(|a, b| b.references.cmp(&a.references))
+            .take(args.top)
+            .collect(),
+        longest_chains: graph.longest_chains().into_iter().take(args.top).collect(),
+    };
+
+    let contents = match format {
+        ReportFormat::Text => text(&analysis),
+        ReportFormat::Json => serde_json::to_string_pretty(&analysis)? + "\n",
+    };
+    write_output(&args.output, &contents)
+}
+
+fn text(analysis: &Analysis) -> String {
+    let mut lines = vec![format!("Orphan tables ({}):", analysis.orphan_tables.len())];
+    lines.extend(analysis.orphan_tables.iter().map(|t| format!("  {}", t)));
+
+    lines.push(format!("Cycles ({}):", analysis.cycles.len()));
+    lines.extend(
+        analysis
+            .cycles
+            .iter()
+            .map(|cycle| format!("  {}", cycle.join(" -> "))),
+    );
+
+    lines.push("Most referenced tables:".to_string());
+    lines.extend(
+        analysis
+            .most_referenced_tables
+            .iter()
+            .map(|t| format!("  {} ({})", t.table, t.references)),
+    );
+
+    lines.push("Longest chains:".to_string());
+    lines.extend(
+        analysis
+            .longest_chains
+            .iter()
+            .map(|chain| format!("  {} ({})", chain.join(" -> "), chain.len() - 1)),
+    );
+
+    lines.join("\n") + "\n"
+}
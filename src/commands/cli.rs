@@ -1,4 +1,7 @@
-use crate::commands::GenerateSubcommands;
+use crate::commands::{
+    AnalyzeArgs, GenerateSubcommands, ImpactArgs, LintArgs, OrderArgs, ReportSubcommands,
+    SubsetArgs, ValidateArgs,
+};
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
@@ -19,4 +22,40 @@ pub enum Commands {
         #[clap(subcommand)]
         command: GenerateSubcommands,
     },
+    #[clap(about = "Lint the database schema with detected relations")]
+    Lint {
+        #[clap(flatten)]
+        args: LintArgs,
+    },
+    #[clap(about = "Validate a tbls configuration against the database schema")]
+    Validate {
+        #[clap(flatten)]
+        args: ValidateArgs,
+    },
+    #[clap(about = "Analyze the graph of relations for orphans, cycles and hub tables")]
+    Analyze {
+        #[clap(flatten)]
+        args: AnalyzeArgs,
+    },
+    #[clap(about = "Order tables so that parents come before children")]
+    Order {
+        #[clap(flatten)]
+        args: OrderArgs,
+    },
+    #[clap(about = "Extract a subset of rows following relations from a root table")]
+    Subset {
+        #[clap(flatten)]
+        args: SubsetArgs,
+    },
+    #[clap(about = "List relations depending on a table or a column")]
+    Impact {
+        #[clap(flatten)]
+        args: ImpactArgs,
+    },
+    #[clap(about = "Data quality reports of relations")]
+    #[clap(arg_required_else_help = true)]
+    Report {
+        #[clap(subcommand)]
+        command: ReportSubcommands,
+    },
 }
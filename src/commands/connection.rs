@@ -0,0 +1,167 @@
+use crate::configs::DatabaseConfig;
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use sqlx::mysql::{MySqlConnectOptions, MySqlPool, MySqlPoolOptions};
+use sqlx::ConnectOptions;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+
+/// Environment variables that are looked up in order when no database URL is given
+const DATABASE_URL_ENVS: [&str; 2] = ["RELATIONS_DATABASE_URL", "DATABASE_URL"];
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct ConnectionArgs {
+    /// Database URL
+    /// By default, RELATIONS_DATABASE_URL or DATABASE_URL environment variable is used.
+    #[clap(long)]
+    pub database_url: Option<String>,
+
+    /// File containing the database URL
+    #[clap(long, conflicts_with = "database-url")]
+    pub database_url_file: Option<PathBuf>,
+
+    /// File containing the password combined with the database URL
+    #[clap(long)]
+    pub password_file: Option<PathBuf>,
+
+    /// Read the password combined with the database URL from stdin
+    #[clap(long, conflicts_with = "password-file")]
+    pub password_stdin: bool,
+}
+
+impl ConnectionArgs {
+    /// Resolve the database URL from the arguments, environment variables and secret files.
+    /// The database section of the project configuration is used when none of them are given.
+    pub(crate) fn database_url(&self, config: &DatabaseConfig) -> Result<Url> {
+        self.resolve(config, false)
+    }
+
+    /// Resolve the database URL like `database_url`, except that the URL of the configuration takes
+    /// precedence over the environment variables.
+    pub(crate) fn database_url_before_env(&self, config: &DatabaseConfig) -> Result<Url> {
+        self.resolve(config, true)
+    }
+
+    fn resolve(&self, config: &DatabaseConfig, is_config_first: bool) -> Result<Url> {
+        let from_env = || {
+            DATABASE_URL_ENVS
+                .iter()
+                .find_map(|name| env::var(name).ok())
+        };
+        let from_config = || -> Result<Option<String>> {
+            Ok(match (&config.url, &config.url_file) {
+                (Some(database_url), _) => Some(database_url.clone()),
+                (None, Some(path)) => Some(read_secret(fs::read_to_string(path)?)),
+                (None, None) => None,
+            })
+        };
+        let database_url = match (&self.database_url, &self.database_url_file) {
+            (Some(database_url), _) => Some(database_url.clone()),
+            (None, Some(path)) => Some(read_secret(fs::read_to_string(path)?)),
+            (None, None) if is_config_first => match from_config()? {
+                Some(database_url) => Some(database_url),
+                None => from_env(),
+            },
+            (None, None) => match from_env() {
+                Some(database_url) => Some(database_url),
+                None => from_config()?,
+            },
+        };
+        let database_url = database_url.ok_or_else(|| {
+            anyhow!(
+                "database URL is required: use --database-url, --database-url-file or {}",
+                DATABASE_URL_ENVS.join(", ")
+            )
+        })?;
+        let mut database_url = Url::parse(database_url.as_str())?;
+
+        let password = match (&self.password_file, &config.password_file) {
+            (Some(path), _) => Some(read_secret(fs::read_to_string(path)?)),
+            (None, _) if self.password_stdin => {
+                let mut line = String::new();
+                io::stdin().lock().read_line(&mut line)?;
+                Some(read_secret(line))
+            }
+            (None, Some(path)) => Some(read_secret(fs::read_to_string(path)?)),
+            (None, None) => None,
+        };
+        if let Some(password) = password {
+            database_url
+                .set_password(Some(password.as_str()))
+                .map_err(|_| anyhow!("cannot set a password to the database URL"))?;
+        }
+
+        Ok(database_url)
+    }
+}
+
+/// Connect to the database of the URL with the backend options of the project configuration.
+pub(crate) async fn connect(database_url: &Url, config: &DatabaseConfig) -> Result<MySqlPool> {
+    match database_url.scheme() {
+        "mysql" => {
+            let opt = MySqlConnectOptions::from_str(database_url.as_str())?
+                .disable_statement_logging()
+                .clone();
+            let mut pool = MySqlPoolOptions::new();
+            if let Some(max_connections) = config.max_connections {
+                pool = pool.max_connections(max_connections);
+            }
+            if let Some(connect_timeout) = config.connect_timeout {
+                pool = pool.acquire_timeout(Duration::from_secs(connect_timeout));
+            }
+            Ok(pool.connect_with(opt).await?)
+        }
+        _ => bail!("unsupported database: `{}`", database_url.as_str()),
+    }
+}
+
+fn read_secret(s: String) -> String {
+    s.trim_end_matches(&['\r', '\n'][..]).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn database_url_precedence() {
+        let config = DatabaseConfig {
+            url: Some("mysql://config/app".to_string()),
+            ..Default::default()
+        };
+        let mut args = ConnectionArgs {
+            database_url: None,
+            database_url_file: None,
+            password_file: None,
+            password_stdin: false,
+        };
+        env::set_var("RELATIONS_DATABASE_URL", "mysql://env/app");
+
+        assert_eq!(
+            args.database_url(&config).unwrap().as_str(),
+            "mysql://env/app"
+        );
+        assert_eq!(
+            args.database_url_before_env(&config).unwrap().as_str(),
+            "mysql://config/app"
+        );
+        assert_eq!(
+            args.database_url_before_env(&DatabaseConfig::default())
+                .unwrap()
+                .as_str(),
+            "mysql://env/app"
+        );
+        args.database_url = Some("mysql://args/app".to_string());
+        assert_eq!(
+            args.database_url_before_env(&config).unwrap().as_str(),
+            "mysql://args/app"
+        );
+
+        env::remove_var("RELATIONS_DATABASE_URL");
+    }
+}
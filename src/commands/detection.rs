@@ -0,0 +1,411 @@
+use crate::commands::{connect, ConnectionArgs};
+use crate::configs::ProjectConfig;
+use crate::databases::{
+    comment_hint_relations, declared_relations, dedup_relations, forced_relations, mysql,
+    relations, rule_compatible_data_type, rule_ends_with, rule_ends_with_excepting_the_prefixes,
+    rule_forbidden_relations, rule_ignore_columns, rule_same_data_type, rule_table_filters,
+    DataTypeCompatibility, Relation, RelationDefinition, Rule as DetectRule, Table, TableFilter,
+};
+use anyhow::{anyhow, Result};
+use clap::Args;
+use itertools::Itertools;
+use regex::Regex;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::str::FromStr;
+use url::Url;
+
+/// Default annotation syntax of the comment-hint detection such as `ref: users.id`
+const COMMENT_HINT_PATTERN: &str = r"ref:\s*(?P<table>[\w$]+)\.(?P<column>[\w$]+)";
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Rule {
+    EndsWith,
+    EndsWithExceptingThePrefixes,
+    SomeDataType,
+    CompatibleDataType,
+}
+
+impl FromStr for Rule {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "ends-with" => Ok(Rule::EndsWith),
+            "ends-with-excepting-the-prefixes" => Ok(Rule::EndsWithExceptingThePrefixes),
+            "some-data-type" => Ok(Rule::SomeDataType),
+            "compatible-data-type" => Ok(Rule::CompatibleDataType),
+            _ => Err(Self::Err::from(format!("unsupported rule: `{}`", s))),
+        }
+    }
+}
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct DetectionArgs {
+    /// Project configuration file
+    /// By default, .relations.yml in the current directory is used if it exists.
+    #[clap(long)]
+    pub config: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Rules for detecting relations. (ends-with, ends-with-excepting-the-prefixes, some-data-type, compatible-data-type)
+    /// By default, column names that end with the table_name_column_name of the parent table are detected as relations.
+    #[clap(short, long)]
+    pub rules: Vec<String>,
+
+    /// Prefixes to be specified for detection by the ends-with-excepting-the-prefixes rule
+    #[clap(long)]
+    pub ends_with_excepting_prefixes: Vec<String>,
+
+    /// Compatibility of the data types required by the compatible-data-type rule (strict, lenient)
+    /// By default, strict is used.
+    #[clap(long)]
+    pub data_type_compatibility: Option<String>,
+
+    /// Glob patterns of tables to introspect, matching `database.table` or `table`
+    #[clap(long)]
+    pub include: Vec<String>,
+
+    /// Glob patterns of tables to ignore, matching `database.table` or `table`
+    #[clap(long)]
+    pub exclude: Vec<String>,
+
+    /// Glob patterns of tables allowed as the parent side of relations
+    #[clap(long)]
+    pub include_parent: Vec<String>,
+
+    /// Glob patterns of tables not allowed as the parent side of relations
+    #[clap(long)]
+    pub exclude_parent: Vec<String>,
+
+    /// Glob patterns of tables allowed as the child side of relations
+    #[clap(long)]
+    pub include_child: Vec<String>,
+
+    /// Glob patterns of tables not allowed as the child side of relations
+    #[clap(long)]
+    pub exclude_child: Vec<String>,
+
+    /// Detect relations annotated in column comments such as `ref: users.id`, taking precedence over the rules
+    #[clap(long)]
+    pub comment_hint: bool,
+
+    /// Regular expression of the comment-hint annotation, capturing the `table` and `column` named groups
+    /// By default, `ref:\s*(?P<table>[\w$]+)\.(?P<column>[\w$]+)` is used.
+    #[clap(long)]
+    pub comment_hint_pattern: Option<String>,
+
+    /// Glob patterns of child columns never detected as relations, matching `table.column` or `column`
+    #[clap(long)]
+    pub ignore_column: Vec<String>,
+
+    /// Pairs of child and parent table glob patterns never detected as relations (e.g. audit_logs->*)
+    #[clap(long)]
+    pub forbid_relation: Vec<String>,
+
+    /// Relations added regardless of the rules (e.g. posts.author_id->users.id)
+    /// Composite columns are separated by commas (e.g. a.x,y->b.x,y), and tables may be qualified by
+    /// the database (e.g. app.posts.author_id->auth.users.id).
+    #[clap(long)]
+    pub force_relation: Vec<String>,
+}
+
+/// Detection settings resolved from the arguments and the project configuration
+pub(crate) struct Detection {
+    pub(crate) config: ProjectConfig,
+    pub(crate) database_url: Url,
+    pub(crate) database_names: HashSet<String>,
+    pub(crate) filter: TableFilter,
+    pub(crate) rules: Vec<DetectRule>,
+    pub(crate) forced_relations: Vec<RelationDefinition>,
+    pub(crate) comment_hint: Option<Regex>,
+}
+
+impl DetectionArgs {
+    /// Resolve the detection settings. Arguments take precedence over the project configuration.
+    pub(crate) fn resolve(self) -> Result<Detection> {
+        let config = ProjectConfig::load(self.config.as_deref())?;
+
+        let database_url = self.connection.database_url(&config.database)?;
+        let database_names = database_url
+            .path_segments()
+            .unwrap()
+            .map(|s| s.to_string())
+            .collect();
+
+        let ends_with_excepting_prefixes = or_config(
+            self.ends_with_excepting_prefixes,
+            &config.ends_with_excepting_prefixes,
+        )
+        .into_iter()
+        .unique()
+        .collect::<HashSet<String>>();
+        let filter = TableFilter::new(
+            or_config(self.include, &config.include),
+            or_config(self.exclude, &config.exclude),
+        );
+        let parent_filter = TableFilter::new(
+            or_config(self.include_parent, &config.parent.include),
+            or_config(self.exclude_parent, &config.parent.exclude),
+        );
+        let child_filter = TableFilter::new(
+            or_config(self.include_child, &config.child.include),
+            or_config(self.exclude_child, &config.child.exclude),
+        );
+
+        let data_type_compatibility = DataTypeCompatibility::from_str(
+            &self
+                .data_type_compatibility
+                .or_else(|| config.data_type_compatibility.clone())
+                .unwrap_or_else(|| "strict".to_string()),
+        )
+        .map_err(|e| anyhow::Error::msg(e.to_string()))?;
+
+        let mut rules = if !self.rules.is_empty() {
+            self.rules
+        } else if !config.rules.is_empty() {
+            config.rules.clone()
+        } else {
+            vec!["ends-with".to_string()]
+        }
+        .into_iter()
+        .unique()
+        .map(|s| {
+            Rule::from_str(&s)
+                .map(|rule| match rule {
+                    Rule::EndsWith => rule_ends_with(),
+                    Rule::EndsWithExceptingThePrefixes => {
+                        rule_ends_with_excepting_the_prefixes(ends_with_excepting_prefixes.clone())
+                    }
+                    Rule::SomeDataType => rule_same_data_type(),
+                    Rule::CompatibleDataType => rule_compatible_data_type(data_type_compatibility),
+                })
+                .map_err(|e| anyhow::Error::msg(e.to_string()))
+        })
+        .collect::<Result<Vec<DetectRule>>>()?;
+        rules.push(rule_table_filters(parent_filter, child_filter));
+
+        rules.push(rule_ignore_columns(or_config(
+            self.ignore_column,
+            &config.ignore_columns,
+        )));
+        let forbidden_relations = if self.forbid_relation.is_empty() {
+            config
+                .forbidden_relations
+                .iter()
+                .map(|r| (r.table.clone(), r.parent_table.clone()))
+                .collect()
+        } else {
+            self.forbid_relation
+                .iter()
+                .map(|s| parse_table_pair(s))
+                .collect::<Result<Vec<(String, String)>>>()?
+        };
+        rules.push(rule_forbidden_relations(forbidden_relations));
+
+        let forced_relations = if self.force_relation.is_empty() {
+            config
+                .relations
+                .iter()
+                .map(|r| {
+                    check_relation_definition(RelationDefinition {
+                        table: r.table.clone(),
+                        columns: r.columns.clone(),
+                        parent_table: r.parent_table.clone(),
+                        parent_columns: r.parent_columns.clone(),
+                    })
+                })
+                .collect::<Result<Vec<RelationDefinition>>>()?
+        } else {
+            self.force_relation
+                .iter()
+                .map(|s| parse_relation_definition(s))
+                .collect::<Result<Vec<RelationDefinition>>>()?
+        };
+
+        let comment_hint = if self.comment_hint
+            || self.comment_hint_pattern.is_some()
+            || config.comment_hint.enabled.unwrap_or(false)
+        {
+            let pattern = self
+                .comment_hint_pattern
+                .or_else(|| config.comment_hint.pattern.clone())
+                .unwrap_or_else(|| COMMENT_HINT_PATTERN.to_string());
+            Some(Regex::new(&pattern)?)
+        } else {
+            None
+        };
+
+        Ok(Detection {
+            config,
+            database_url,
+            database_names,
+            filter,
+            rules,
+            forced_relations,
+            comment_hint,
+        })
+    }
+}
+
+impl Detection {
+    /// Introspect the tables of the databases in the URL path.
+    pub(crate) async fn tables(&self) -> Result<Vec<Table>> {
+        let conn = connect(&self.database_url, &self.config.database).await?;
+        mysql::tables(&conn, self.database_names.clone(), &self.filter).await
+    }
+
+    /// Detect relations between the tables with the rules and the comment hints, and add the forced relations.
+    /// Relations declared as foreign keys are included, and detected relations that duplicate them are dropped.
+    pub(crate) fn relations(&self, tables: Vec<Table>) -> Result<Vec<Relation>> {
+        let declared = declared_relations(&tables);
+        let forced = forced_relations(&tables, &self.forced_relations)?;
+        let hinted = match &self.comment_hint {
+            Some(pattern) => comment_hint_relations(&tables, pattern),
+            None => Vec::new(),
+        };
+        // Columns annotated in comments are related only as annotated
+        let detected = relations(tables, &self.rules)
+            .into_iter()
+            .filter(|r| {
+                !hinted.iter().any(|h| {
+                    h.table.name == r.table.name
+                        && r.columns.iter().any(|c| c.name == h.columns[0].name)
+                })
+            })
+            .collect::<Vec<Relation>>();
+
+        for r in forced.iter().chain(hinted.iter()).chain(detected.iter()) {
+            for (column, parent_column) in r.columns.iter().zip(r.parent_columns.iter()) {
+                if column
+                    .parsed_data_type()
+                    .is_signedness_mismatch(&parent_column.parsed_data_type())
+                {
+                    log::warn!(
+                        "{} pairs signed and unsigned keys ({} and {})",
+                        r.definition(),
+                        column.data_type,
+                        parent_column.data_type
+                    );
+                }
+            }
+        }
+
+        Ok(dedup_relations(
+            declared
+                .into_iter()
+                .chain(forced)
+                .chain(hinted)
+                .chain(detected)
+                .collect(),
+        ))
+    }
+}
+
+/// Values of the argument, or of the project configuration if the argument is not given
+fn or_config(args: Vec<String>, config: &[String]) -> Vec<String> {
+    if args.is_empty() {
+        config.to_vec()
+    } else {
+        args
+    }
+}
+
+/// Parse `child_table->parent_table`
+pub(crate) fn parse_table_pair(s: &str) -> Result<(String, String)> {
+    s.split_once("->")
+        .map(|(table, parent_table)| (table.trim().to_string(), parent_table.trim().to_string()))
+        .ok_or_else(|| anyhow!("invalid relation: `{}`", s))
+}
+
+/// Parse `[database.]table.column[,column]->[database.]parent_table.column[,column]`
+fn parse_relation_definition(s: &str) -> Result<RelationDefinition> {
+    let parse_side = |side: &str| {
+        side.trim()
+            .rsplit_once('.')
+            .map(|(table, columns)| {
+                (
+                    table.to_string(),
+                    columns.split(',').map(|c| c.trim().to_string()).collect(),
+                )
+            })
+            .ok_or_else(|| anyhow!("invalid relation: `{}`", s))
+    };
+    let (child, parent) = s
+        .split_once("->")
+        .ok_or_else(|| anyhow!("invalid relation: `{}`", s))?;
+    let (table, columns) = parse_side(child)?;
+    let (parent_table, parent_columns) = parse_side(parent)?;
+
+    check_relation_definition(RelationDefinition {
+        table,
+        columns,
+        parent_table,
+        parent_columns,
+    })
+}
+
+/// Check that the relation names its columns, and as many on the child side as on the parent side.
+fn check_relation_definition(definition: RelationDefinition) -> Result<RelationDefinition> {
+    let text = || {
+        format!(
+            "{}.{}->{}.{}",
+            definition.table,
+            definition.columns.join(","),
+            definition.parent_table,
+            definition.parent_columns.join(",")
+        )
+    };
+    if definition.table.is_empty()
+        || definition.parent_table.is_empty()
+        || definition.columns.is_empty()
+        || definition
+            .columns
+            .iter()
+            .chain(definition.parent_columns.iter())
+            .any(|c| c.is_empty())
+    {
+        return Err(anyhow!("invalid relation: `{}`", text()));
+    }
+    if definition.columns.len() != definition.parent_columns.len() {
+        return Err(anyhow!("number of columns does not match: `{}`", text()));
+    }
+
+    Ok(definition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relation_definition() {
+        assert_eq!(
+            parse_relation_definition("app.posts.a,b -> users.x, y").unwrap(),
+            RelationDefinition {
+                table: "app.posts".to_string(),
+                columns: vec!["a".to_string(), "b".to_string()],
+                parent_table: "users".to_string(),
+                parent_columns: vec!["x".to_string(), "y".to_string()],
+            }
+        );
+        assert!(parse_relation_definition("posts.a,b->users.x").is_err());
+        assert!(parse_relation_definition("posts.->users.x").is_err());
+        assert!(parse_relation_definition("posts->users.x").is_err());
+    }
+
+    #[test]
+    fn relation_definition_of_config() {
+        let definition = |columns: &[&str], parent_columns: &[&str]| RelationDefinition {
+            table: "posts".to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            parent_table: "users".to_string(),
+            parent_columns: parent_columns.iter().map(|c| c.to_string()).collect(),
+        };
+        assert!(check_relation_definition(definition(&["user_id"], &["id"])).is_ok());
+        assert!(check_relation_definition(definition(&[], &[])).is_err());
+        assert!(check_relation_definition(definition(&["user_id"], &[])).is_err());
+        assert!(check_relation_definition(definition(&["a", "b"], &["id"])).is_err());
+    }
+}
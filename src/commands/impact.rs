@@ -0,0 +1,173 @@
+use crate::commands::{write_output, DetectionArgs, ReportFormat};
+use crate::databases::Relation;
+use anyhow::{bail, Result};
+use clap::Args;
+use serde::Serialize;
+use std::collections::{BTreeSet, VecDeque};
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct ImpactArgs {
+    #[clap(flatten)]
+    detection: DetectionArgs,
+
+    /// Table to be changed
+    #[clap(long)]
+    table: String,
+
+    /// Column to be changed
+    /// By default, relations referencing any column of the table are followed.
+    #[clap(long)]
+    column: Option<String>,
+
+    /// Number of relation hops to follow transitively
+    #[clap(long, default_value = "3")]
+    depth: usize,
+
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: String,
+
+    /// Output file path, or `-` for stdout
+    #[clap(short, long, default_value = "-")]
+    output: String,
+}
+
+/// Child relation depending on the changed table or column
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Impact {
+    depth: usize,
+    table: String,
+    columns: Vec<String>,
+    parent_table: String,
+    parent_columns: Vec<String>,
+    is_virtual: bool,
+}
+
+pub async fn run_impact(args: ImpactArgs) -> Result<()> {
+    let format = ReportFormat::parse(&args.format)?;
+    let detection = args.detection.resolve()?;
+    let tables = detection.tables().await?;
+    let table = match tables.iter().find(|t| t.name == args.table) {
+        Some(table) => table,
+        None => bail!("table not found: `{}`", args.table),
+    };
+    if let Some(column) = &args.column {
+        if !table.columns.iter().any(|c| &c.name == column) {
+            bail!("column not found: `{}.{}`", args.table, column);
+        }
+    }
+    let relations = detection.relations(tables.clone())?;
+
+    let impacts = impacts(&relations, &args.table, args.column.as_deref(), args.depth);
+
+    let contents = match format {
+        ReportFormat::Text => text(&args.table, args.column.as_deref(), &impacts),
+        ReportFormat::Json => serde_json::to_string_pretty(&impacts)? + "\n",
+    };
+    write_output(&args.output, &contents)
+}
+
+/// Child relations of the table reached within the depth, in breadth-first order
+/// When the column is given, only the relations referencing the column, and then the child columns
+/// of those relations, are followed.
+fn impacts(relations: &[Relation], table: &str, column: Option<&str>, depth: usize) -> Vec<Impact> {
+    let mut impacts = Vec::new();
+    let mut visited = BTreeSet::new();
+    let mut queue = VecDeque::from([(table.to_string(), column.map(|c| vec![c.to_string()]), 0)]);
+    while let Some((table, columns, d)) = queue.pop_front() {
+        if d >= depth || !visited.insert((table.clone(), columns.clone())) {
+            continue;
+        }
+        for r in relations.iter().filter(|r| {
+            r.parent_table.name == table
+                && match &columns {
+                    Some(columns) => r.parent_columns.iter().any(|c| columns.contains(&c.name)),
+                    None => true,
+                }
+        }) {
+            let child_columns = r
+                .columns
+                .iter()
+                .map(|c| c.name.clone())
+                .collect::<Vec<String>>();
+            impacts.push(Impact {
+                depth: d + 1,
+                table: r.table.name.clone(),
+                columns: child_columns.clone(),
+                parent_table: r.parent_table.name.clone(),
+                parent_columns: r.parent_columns.iter().map(|c| c.name.clone()).collect(),
+                is_virtual: r.is_virtual,
+            });
+            queue.push_back((
+                r.table.name.clone(),
+                columns.as_ref().map(|_| child_columns),
+                d + 1,
+            ));
+        }
+    }
+
+    impacts
+}
+
+/// Render the impacts one per line in breadth-first order, prefixed with the number of hops from
+/// the changed table
+fn text(table: &str, column: Option<&str>, impacts: &[Impact]) -> String {
+    let mut lines = vec![match column {
+        Some(column) => format!("{}({})", table, column),
+        None => table.to_string(),
+    }];
+    lines.extend(impacts.iter().map(|i| {
+        format!(
+            "{}\t{}({}) -> {}({}) [{}]",
+            i.depth,
+            i.table,
+            i.columns.join(", "),
+            i.parent_table,
+            i.parent_columns.join(", "),
+            if i.is_virtual {
+                "virtual"
+            } else {
+                "foreign key"
+            }
+        )
+    }));
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::{relation, table};
+
+    #[test]
+    fn text_with_depth_column() {
+        let tables = vec![
+            table("users", &["id"]),
+            table("posts", &["id", "user_id"]),
+            table("comments", &["id", "post_id", "user_id"]),
+        ];
+        let relations = vec![
+            relation(&tables, "posts", &["user_id"], "users", &["id"], false),
+            relation(&tables, "comments", &["user_id"], "users", &["id"], true),
+            relation(&tables, "comments", &["post_id"], "posts", &["id"], false),
+        ];
+        let impacts = impacts(&relations, "users", Some("id"), 3);
+        assert_eq!(
+            text("users", Some("id"), &impacts),
+            "users(id)\n\
+             1\tposts(user_id) -> users(id) [foreign key]\n\
+             1\tcomments(user_id) -> users(id) [virtual]\n"
+        );
+
+        let impacts = super::impacts(&relations, "users", None, 3);
+        assert_eq!(
+            text("users", None, &impacts),
+            "users\n\
+             1\tposts(user_id) -> users(id) [foreign key]\n\
+             1\tcomments(user_id) -> users(id) [virtual]\n\
+             2\tcomments(post_id) -> posts(id) [foreign key]\n"
+        );
+    }
+}
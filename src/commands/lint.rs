@@ -0,0 +1,178 @@
+use crate::commands::{write_output, DetectionArgs, ReportFormat, STDOUT};
+use crate::configs::{AdditionalRelation, Config};
+use crate::databases::{dedup_relations, forced_relations, Relation, RelationDefinition, Table};
+use crate::lints::check_lint;
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use std::path::PathBuf;
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct LintArgs {
+    #[clap(flatten)]
+    detection: DetectionArgs,
+
+    /// tbls configuration file whose lint section is evaluated
+    /// By default, .tbls.yml, .tbls.yaml or .tbl.yml in the current directory is used.
+    #[clap(long)]
+    tbls_config: Option<PathBuf>,
+
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: String,
+}
+
+pub async fn run_lint(args: LintArgs) -> Result<()> {
+    let format = ReportFormat::parse(&args.format)?;
+    let path = args
+        .tbls_config
+        .or_else(Config::find)
+        .ok_or_else(|| anyhow!("tbls configuration file is required: use --tbls-config"))?;
+    let config = Config::load(&path)?;
+    let lint = config
+        .lint
+        .ok_or_else(|| anyhow!("lint section not found in `{}`", path.display()))?;
+
+    let detection = args.detection.resolve()?;
+    let tables = detection.tables().await?;
+    let forced = forced_relations(&tables, &detection.forced_relations)?;
+    let configured = configured_relations(&tables, config.relations.as_deref().unwrap_or_default());
+    let relations = dedup_relations(
+        detection
+            .relations(tables.clone())?
+            .into_iter()
+            .chain(configured.clone())
+            .collect(),
+    );
+
+    // The relations of the project configuration are copied to the tbls configuration by
+    // `generate config`, so each file is checked for duplicates separately
+    let violations = check_lint(&lint, &tables, &relations, &[forced, configured]);
+
+    let contents = match format {
+        ReportFormat::Text => violations
+            .iter()
+            .map(|v| format!("[{}] {}\n", v.rule, v.message))
+            .collect::<String>(),
+        ReportFormat::Json => serde_json::to_string_pretty(&violations)? + "\n",
+    };
+    write_output(STDOUT, &contents)?;
+
+    if !violations.is_empty() {
+        bail!("{} violation(s) found", violations.len());
+    }
+
+    Ok(())
+}
+
+/// Relations of the tbls configuration
+/// Relations that do not resolve are skipped, since they are reported by validate.
+pub(crate) fn configured_relations(
+    tables: &[Table],
+    relations: &[AdditionalRelation],
+) -> Vec<Relation> {
+    relations
+        .iter()
+        .filter_map(|relation| {
+            let definition = RelationDefinition {
+                table: relation.table.clone(),
+                columns: relation.columns.clone(),
+                parent_table: relation.parent_table.clone(),
+                parent_columns: relation.parent_columns.clone(),
+            };
+            forced_relations(tables, &[definition])
+                .map_err(|e| log::debug!("skipped relation of the tbls configuration: {}", e))
+                .ok()
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::additional_relations;
+    use crate::configs::{DuplicateRelations, Lint};
+    use crate::databases::testing::{relation, table, with_foreign_key};
+    use crate::databases::{declared_relations, relations, rule_ends_with};
+
+    fn tables() -> Vec<Table> {
+        vec![
+            table("users", &["id", "name"]),
+            with_foreign_key(
+                table("posts", &["id", "users_id"]),
+                &["users_id"],
+                "users",
+                &["id"],
+            ),
+            table("comments", &["id", "posts_id", "users_id"]),
+        ]
+    }
+
+    fn lint() -> Lint {
+        Lint {
+            duplicate_relations: Some(DuplicateRelations {
+                enabled: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Relations of the foreign keys and the ends-with rule
+    fn detected(tables: &[Table]) -> Vec<Relation> {
+        dedup_relations(
+            declared_relations(tables)
+                .into_iter()
+                .chain(relations(tables.to_vec(), &[rule_ends_with()]))
+                .collect(),
+        )
+    }
+
+    fn violations(tables: &[Table], relations: &[AdditionalRelation]) -> Vec<String> {
+        let configured = configured_relations(tables, relations);
+        let relations = dedup_relations(
+            detected(tables)
+                .into_iter()
+                .chain(configured.clone())
+                .collect(),
+        );
+        check_lint(&lint(), tables, &relations, &[Vec::new(), configured])
+            .into_iter()
+            .map(|v| v.message)
+            .collect()
+    }
+
+    #[test]
+    fn generated_config_has_no_duplicate_relations() {
+        let tables = tables();
+        let config = Config {
+            relations: Some(additional_relations(&detected(&tables))),
+            ..Default::default()
+        };
+        let config: Config =
+            serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+        let generated = config.relations.unwrap();
+        assert_eq!(generated.len(), 2);
+
+        assert!(violations(&tables, &generated).is_empty());
+    }
+
+    #[test]
+    fn duplicate_relations_of_config() {
+        let tables = tables();
+        let relations = [
+            relation(&tables, "posts", &["users_id"], "users", &["id"], true),
+            relation(&tables, "comments", &["posts_id"], "posts", &["id"], true),
+            relation(&tables, "comments", &["posts_id"], "posts", &["id"], true),
+        ];
+        let config = additional_relations(&relations);
+
+        assert_eq!(
+            violations(&tables, &config),
+            vec![
+                "relation `comments(posts_id) -> posts(id)` is duplicated".to_string(),
+                "relation `posts(users_id) -> users(id)` duplicates a foreign key".to_string(),
+            ]
+        );
+    }
+}
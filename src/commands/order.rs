@@ -0,0 +1,81 @@
+use crate::commands::{write_output, DetectionArgs, ReportFormat};
+use crate::databases::Graph;
+use anyhow::Result;
+use clap::Args;
+use serde::Serialize;
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct OrderArgs {
+    #[clap(flatten)]
+    detection: DetectionArgs,
+
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: String,
+
+    /// Output file path, or `-` for stdout
+    #[clap(short, long, default_value = "-")]
+    output: String,
+}
+
+/// Orders of the tables to insert and delete rows without violating the relations
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Order {
+    insert: Vec<String>,
+    delete: Vec<String>,
+    ignored_relations: Vec<IgnoredRelation>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IgnoredRelation {
+    table: String,
+    parent_table: String,
+}
+
+pub async fn run_order(args: OrderArgs) -> Result<()> {
+    let format = ReportFormat::parse(&args.format)?;
+    let detection = args.detection.resolve()?;
+    let tables = detection.tables().await?;
+    let relations = detection.relations(tables.clone())?;
+
+    let (insert, ignored) = Graph::new(&tables, &relations).topological_order();
+    let order = Order {
+        delete: insert.iter().rev().cloned().collect(),
+        insert,
+        ignored_relations: ignored
+            .into_iter()
+            .map(|(table, parent_table)| IgnoredRelation {
+                table,
+                parent_table,
+            })
+            .collect(),
+    };
+
+    let contents = match format {
+        ReportFormat::Text => text(&order),
+        ReportFormat::Json => serde_json::to_string_pretty(&order)? + "\n",
+    };
+    write_output(&args.output, &contents)
+}
+
+fn text(order: &Order) -> String {
+    let mut lines = vec!["Insert order:".to_string()];
+    lines.extend(order.insert.iter().map(|t| format!("  {}", t)));
+
+    lines.push("Delete order:".to_string());
+    lines.extend(order.delete.iter().map(|t| format!("  {}", t)));
+
+    if !order.ignored_relations.is_empty() {
+        lines.push("Ignored relations:".to_string());
+        lines.extend(
+            order
+                .ignored_relations
+                .iter()
+                .map(|r| format!("  {} -> {}", r.table, r.parent_table)),
+        );
+    }
+
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+/// Output path that writes to stdout
+pub const STDOUT: &str = "-";
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum OutputFormat {
+    Yaml,
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "yaml" => Ok(OutputFormat::Yaml),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(Self::Err::from(format!("unsupported format: `{}`", s))),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub(crate) fn parse(s: &str) -> Result<OutputFormat> {
+        OutputFormat::from_str(s).map_err(|e| anyhow::Error::msg(e.to_string()))
+    }
+
+    pub(crate) fn serialize<T: Serialize>(&self, value: &T) -> Result<String> {
+        match self {
+            OutputFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+            OutputFormat::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+        }
+    }
+}
+
+/// Format of reports, which are human readable text or machine readable JSON
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            _ => Err(Self::Err::from(format!("unsupported format: `{}`", s))),
+        }
+    }
+}
+
+impl ReportFormat {
+    pub(crate) fn parse(s: &str) -> Result<ReportFormat> {
+        ReportFormat::from_str(s).map_err(|e| anyhow::Error::msg(e.to_string()))
+    }
+}
+
+/// Write the contents to the file, or to stdout if the output is `-`.
+pub(crate) fn write_output(output: &str, contents: &str) -> Result<()> {
+    if output == STDOUT {
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(contents.as_bytes())?;
+        stdout.flush()?;
+    } else {
+        fs::write(output, contents)?;
+    }
+
+    Ok(())
+}
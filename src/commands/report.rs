@@ -0,0 +1,345 @@
+use crate::commands::{connect, write_output, DetectionArgs, ReportFormat};
+use crate::databases::{mysql, Relation};
+use crate::generators::{literal, missing_indexes, Dialect};
+use anyhow::{anyhow, Result};
+use clap::{Args, Subcommand};
+use itertools::Itertools;
+use serde::Serialize;
+use sqlx::MySqlPool;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time;
+
+#[derive(Subcommand, PartialEq, Eq, Debug)]
+pub enum ReportSubcommands {
+    #[clap(about = "Report child rows of virtual relations whose parent rows do not exist")]
+    Orphans {
+        #[clap(flatten)]
+        detection: DetectionArgs,
+
+        /// Number of child rows to check per relation
+        /// By default, all rows are checked.
+        #[clap(long)]
+        sample: Option<usize>,
+
+        /// Number of example keys of the orphan rows to report per relation
+        #[clap(long, default_value = "5")]
+        examples: usize,
+
+        /// Seconds to wait for each query before skipping the relation
+        #[clap(long)]
+        timeout: Option<u64>,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+    #[clap(about = "Report relations whose child columns are not covered by any index")]
+    MissingIndexes {
+        #[clap(flatten)]
+        detection: DetectionArgs,
+
+        /// Output CREATE INDEX statements instead of the report
+        #[clap(long)]
+        sql: bool,
+
+        #[clap(flatten)]
+        report: ReportArgs,
+    },
+}
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct ReportArgs {
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: String,
+
+    /// Output file path, or `-` for stdout
+    #[clap(short, long, default_value = "-")]
+    output: String,
+}
+
+/// Orphan rows of a relation
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Orphans {
+    relation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    orphans: Option<i64>,
+    examples: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub async fn run_report(command: ReportSubcommands) -> Result<()> {
+    match command {
+        ReportSubcommands::Orphans {
+            detection,
+            sample,
+            examples,
+            timeout,
+            report,
+        } => {
+            let format = ReportFormat::parse(&report.format)?;
+            let detection = detection.resolve()?;
+            let dialect = Dialect::from_scheme(detection.database_url.scheme())?;
+            let tables = detection.tables().await?;
+            let relations = detection.relations(tables)?;
+            let conn = connect(&detection.database_url, &detection.config.database).await?;
+            let timeout = timeout.map(Duration::from_secs);
+
+            let mut rows = Vec::new();
+            for r in relations
+                .iter()
+                .filter(|r| r.is_virtual)
+                .sorted_by_key(|r| r.definition())
+            {
+                rows.push(orphans(&conn, r, sample, examples, timeout, dialect).await);
+            }
+
+            let contents = match format {
+                ReportFormat::Text => orphans_text(&rows),
+                ReportFormat::Json => serde_json::to_string_pretty(&rows)? + "\n",
+            };
+            write_output(&report.output, &contents)
+        }
+        ReportSubcommands::MissingIndexes {
+            detection,
+            sql,
+            report,
+        } => {
+            let format = ReportFormat::parse(&report.format)?;
+            let detection = detection.resolve()?;
+            let tables = detection.tables().await?;
+            let relations = detection
+                .relations(tables)?
+                .into_iter()
+                .filter(|r| !r.table.has_index_on(&r.columns))
+                .sorted_by_key(|r| r.definition())
+                .collect::<Vec<Relation>>();
+
+            let contents = if sql {
+                let dialect = Dialect::from_scheme(detection.database_url.scheme())?;
+                missing_indexes(&relations, dialect)
+                    .into_iter()
+                    .map(|s| s + "\n")
+                    .collect()
+            } else {
+                let rows = relations
+                    .iter()
+                    .map(|r| MissingIndex {
+                        table: r.table.name.clone(),
+                        columns: r.columns.iter().map(|c| c.name.clone()).collect(),
+                        relation: r.definition(),
+                    })
+                    .collect::<Vec<MissingIndex>>();
+                match format {
+                    ReportFormat::Text => table_text(
+                        &["TABLE", "COLUMNS", "RELATION"],
+                        rows.iter()
+                            .map(|row| {
+                                vec![
+                                    row.table.clone(),
+                                    row.columns.join(", "),
+                                    row.relation.clone(),
+                                ]
+                            })
+                            .collect(),
+                    ),
+                    ReportFormat::Json => serde_json::to_string_pretty(&rows)? + "\n",
+                }
+            };
+            write_output(&report.output, &contents)
+        }
+    }
+}
+
+/// Relation whose child columns are not a left prefix of any index
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MissingIndex {
+    table: String,
+    columns: Vec<String>,
+    relation: String,
+}
+
+async fn orphans(
+    conn: &MySqlPool,
+    relation: &Relation,
+    sample: Option<usize>,
+    examples: usize,
+    timeout: Option<Duration>,
+    dialect: Dialect,
+) -> Orphans {
+    let query = OrphanQuery {
+        relation,
+        sample,
+        timeout,
+        dialect,
+    };
+    let count = match with_timeout(timeout, mysql::select_count(conn, &query.count())).await {
+        Ok(count) => count,
+        Err(e) => {
+            return Orphans {
+                relation: relation.definition(),
+                orphans: None,
+                examples: Vec::new(),
+                error: Some(e.to_string()),
+            }
+        }
+    };
+    // The count is reported even if the examples cannot be selected
+    let (examples, error) = if count > 0 && examples > 0 {
+        match with_timeout(
+            timeout,
+            mysql::select_strings(conn, &query.examples(examples)),
+        )
+        .await
+        {
+            Ok(examples) => (examples, None),
+            Err(e) => (Vec::new(), Some(format!("examples: {}", e))),
+        }
+    } else {
+        (Vec::new(), None)
+    };
+
+    Orphans {
+        relation: relation.definition(),
+        orphans: Some(count),
+        examples,
+        error,
+    }
+}
+
+/// Queries of the child rows whose non-NULL keys have no matching parent row
+struct OrphanQuery<'a> {
+    relation: &'a Relation,
+    sample: Option<usize>,
+    timeout: Option<Duration>,
+    dialect: Dialect,
+}
+
+impl OrphanQuery<'_> {
+    fn count(&self) -> String {
+        format!("SELECT {}COUNT(*) {}", self.hint(), self.from())
+    }
+
+    fn examples(&self, limit: usize) -> String {
+        format!(
+            "SELECT {}DISTINCT CONVERT(CONCAT('(', CONCAT_WS(', ', {}), ')') USING utf8mb4) {} LIMIT {}",
+            self.hint(),
+            self.relation
+                .columns
+                .iter()
+                .map(|c| literal(&format!("c.{}", self.dialect.quote(&c.name)), c))
+                .join(", "),
+            self.from(),
+            limit
+        )
+    }
+
+    /// Optimizer hint aborting the query on the server side as well
+    fn hint(&self) -> String {
+        match self.timeout {
+            Some(timeout) => format!("/*+ MAX_EXECUTION_TIME({}) */ ", timeout.as_millis()),
+            None => String::new(),
+        }
+    }
+
+    fn from(&self) -> String {
+        let r = self.relation;
+        let q = |s: &str| self.dialect.quote(s);
+        let not_null = r
+            .columns
+            .iter()
+            .map(|c| format!("{} IS NOT NULL", q(&c.name)))
+            .join(" AND ");
+        let child = format!("{}.{}", q(&r.table.database), q(&r.table.name));
+        let child = match self.sample {
+            Some(sample) => format!(
+                "(SELECT {} FROM {} WHERE {} LIMIT {})",
+                r.columns.iter().map(|c| q(&c.name)).join(", "),
+                child,
+                not_null,
+                sample
+            ),
+            None => child,
+        };
+        format!(
+            "FROM {} c LEFT JOIN {}.{} p ON {} WHERE {} AND p.{} IS NULL",
+            child,
+            q(&r.parent_table.database),
+            q(&r.parent_table.name),
+            r.columns
+                .iter()
+                .zip(r.parent_columns.iter())
+                .map(|(c, p)| format!("c.{} = p.{}", q(&c.name), q(&p.name)))
+                .join(" AND "),
+            r.columns
+                .iter()
+                .map(|c| format!("c.{} IS NOT NULL", q(&c.name)))
+                .join(" AND "),
+            q(&r.parent_columns[0].name)
+        )
+    }
+}
+
+async fn with_timeout<T>(
+    timeout: Option<Duration>,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    match timeout {
+        Some(timeout) => time::timeout(timeout, future)
+            .await
+            .map_err(|_| anyhow!("timed out after {} seconds", timeout.as_secs()))?,
+        None => future.await,
+    }
+}
+
+fn orphans_text(rows: &[Orphans]) -> String {
+    table_text(
+        &["RELATION", "ORPHANS", "EXAMPLES"],
+        rows.iter()
+            .map(|row| {
+                vec![
+                    row.relation.clone(),
+                    match (&row.orphans, &row.error) {
+                        (Some(count), _) => count.to_string(),
+                        (None, Some(error)) => format!("error: {}", error),
+                        (None, None) => String::new(),
+                    },
+                    match (&row.orphans, &row.error) {
+                        (Some(_), Some(error)) => format!("error: {}", error),
+                        _ => row.examples.join(" "),
+                    },
+                ]
+            })
+            .collect(),
+    )
+}
+
+/// Text table whose columns are aligned to the widest cells
+fn table_text(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let rows = std::iter::once(header.iter().map(|s| s.to_string()).collect())
+        .chain(rows)
+        .collect::<Vec<Vec<String>>>();
+    let widths = (0..header.len())
+        .map(|i| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .join("  ")
+                .trim_end()
+                .to_string()
+                + "\n"
+        })
+        .collect()
+}
@@ -0,0 +1,72 @@
+use crate::commands::{connect, write_output, DetectionArgs};
+use crate::databases::mysql;
+use crate::generators::{subset, Dialect};
+use anyhow::Result;
+use clap::Args;
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct SubsetArgs {
+    #[clap(flatten)]
+    detection: DetectionArgs,
+
+    /// Root table of the subset
+    #[clap(long)]
+    table: String,
+
+    /// Condition of the rows of the root table (e.g. "id = 1")
+    #[clap(long = "where")]
+    condition: String,
+
+    /// Number of relation hops from the root table to follow to child tables
+    /// Parent tables referenced by the rows are always followed.
+    #[clap(long, default_value = "3")]
+    depth: usize,
+
+    /// Output INSERT statements of the rows instead of SELECT statements
+    #[clap(long)]
+    dump: bool,
+
+    /// Output file path, or `-` for stdout
+    #[clap(short, long, default_value = "-")]
+    output: String,
+}
+
+pub async fn run_subset(args: SubsetArgs) -> Result<()> {
+    let detection = args.detection.resolve()?;
+    let dialect = Dialect::from_scheme(detection.database_url.scheme())?;
+    let tables = detection.tables().await?;
+    let relations = detection.relations(tables.clone())?;
+
+    let subset = subset(
+        &tables,
+        &relations,
+        &args.table,
+        &args.condition,
+        args.depth,
+        dialect,
+    )?;
+
+    let mut lines = Vec::new();
+    if args.dump {
+        let conn = connect(&detection.database_url, &detection.config.database).await?;
+        // Rows are inserted in the order of the tables, though cycles may remain
+        // Values are selected as utf8mb4, so they are inserted in the same character set
+        lines.push("SET NAMES utf8mb4;".to_string());
+        lines.push("SET FOREIGN_KEY_CHECKS = 0;".to_string());
+        for s in subset.iter() {
+            let values = mysql::select_strings(&conn, &s.select_values(dialect)).await?;
+            log::debug!("{} rows from {}", values.len(), s.table.name);
+            if !values.is_empty() {
+                lines.push(s.insert(&values, dialect));
+            }
+        }
+        lines.push("SET FOREIGN_KEY_CHECKS = 1;".to_string());
+    } else {
+        for s in subset.iter() {
+            lines.push(format!("-- {} (depth {})", s.table.name, s.depth));
+            lines.push(s.select(dialect));
+        }
+    }
+
+    write_output(&args.output, &(lines.join("\n") + "\n"))
+}
@@ -0,0 +1,85 @@
+use crate::commands::{connect, write_output, ConnectionArgs, ReportFormat, STDOUT};
+use crate::configs::{remove_stale_entries, stale_entries, Config, DatabaseConfig};
+use crate::databases::{mysql, TableFilter};
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use regex::{Captures, Regex};
+use std::collections::HashSet;
+use std::env;
+use std::path::PathBuf;
+
+#[derive(Args, PartialEq, Eq, Debug)]
+pub struct ValidateArgs {
+    /// tbls configuration file to validate
+    /// By default, .tbls.yml, .tbls.yaml or .tbl.yml in the current directory is used.
+    #[clap(long, alias = "tbls-config")]
+    config: Option<PathBuf>,
+
+    // The dsn of the configuration file takes precedence over the environment variables, so that
+    // the file is validated against its own database unless a database URL is given explicitly
+    #[clap(flatten)]
+    connection: ConnectionArgs,
+
+    /// Remove the stale entries and rewrite the configuration file
+    #[clap(long)]
+    fix: bool,
+
+    /// Output format (text, json)
+    #[clap(long, default_value = "text")]
+    format: String,
+}
+
+pub async fn run_validate(args: ValidateArgs) -> Result<()> {
+    let format = ReportFormat::parse(&args.format)?;
+    let path = args
+        .config
+        .or_else(Config::find)
+        .ok_or_else(|| anyhow!("tbls configuration file is required: use --config"))?;
+    let mut config = Config::load(&path)?;
+
+    let database_config = DatabaseConfig {
+        url: (!config.dsn.is_empty()).then(|| expand_env(&config.dsn)),
+        ..Default::default()
+    };
+    let database_url = args.connection.database_url_before_env(&database_config)?;
+    let database_names = database_url
+        .path_segments()
+        .unwrap()
+        .map(|s| s.to_string())
+        .collect::<HashSet<String>>();
+    let conn = connect(&database_url, &database_config).await?;
+    let tables = mysql::tables(&conn, database_names, &TableFilter::default()).await?;
+
+    let entries = stale_entries(&config, &tables);
+
+    let contents = match format {
+        ReportFormat::Text => entries
+            .iter()
+            .map(|e| format!("[{}] {}: {}\n", e.section, e.entry, e.reason))
+            .collect::<String>(),
+        ReportFormat::Json => serde_json::to_string_pretty(&entries)? + "\n",
+    };
+    write_output(STDOUT, &contents)?;
+
+    if args.fix {
+        if !entries.is_empty() {
+            remove_stale_entries(&mut config, &tables);
+            config.save(&path)?;
+            log::debug!("removed {} stale entries", entries.len());
+        }
+    } else if !entries.is_empty() {
+        bail!("{} stale entries found", entries.len());
+    }
+
+    Ok(())
+}
+
+/// Expand `${NAME}` with the environment variable as tbls does, leaving undefined ones as they are
+fn expand_env(s: &str) -> String {
+    Regex::new(r"\$\{(\w+)\}")
+        .unwrap()
+        .replace_all(s, |caps: &Captures| {
+            env::var(&caps[1]).unwrap_or_else(|_| caps[0].to_string())
+        })
+        .into_owned()
+}
@@ -1,3 +1,7 @@
 mod config;
+mod project;
+mod validation;
 
 pub(crate) use config::*;
+pub(crate) use project::*;
+pub(crate) use validation::*;
@@ -1,5 +1,13 @@
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File names of the tbls configuration looked up in the current directory
+/// `.tbl.yml` is the default output of `generate config`.
+const CONFIG_FILES: [&str; 3] = [".tbls.yml", ".tbls.yaml", ".tbl.yml"];
 
 /// Configuration file compatible with .tbls.yml
 /// ```yaml
@@ -20,6 +28,8 @@ use std::collections::HashMap;
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub(crate) struct Config {
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
     pub(crate) name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -28,6 +38,8 @@ pub(crate) struct Config {
     #[serde(default)]
     pub(crate) labels: Option<Vec<String>>,
     pub(crate) dsn: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default)]
     pub(crate) doc_path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -53,6 +65,9 @@ pub(crate) struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) comments: Option<Vec<AdditionalComment>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub(crate) viewpoints: Vec<Viewpoint>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) dict: Option<serde_yaml::Value>,
@@ -68,6 +83,92 @@ pub(crate) struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) required_version: Option<String>,
+    /// Keys unknown to this tool, written back as they are
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
+}
+
+impl Config {
+    /// Load the configuration from the file.
+    pub(crate) fn load(path: &Path) -> Result<Config> {
+        Ok(serde_yaml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Save the configuration to the file.
+    /// Keys keep the order of the existing file, and new keys follow them in the order of the fields.
+    pub(crate) fn save(&self, path: &Path) -> Result<()> {
+        let mut value = serde_yaml::to_value(self)?;
+        if let Some(original) = fs::read_to_string(path)
+            .ok()
+            .and_then(|s| serde_yaml::from_str::<Value>(&s).ok())
+        {
+            value = order_like(value, &original);
+        }
+        fs::write(path, serde_yaml::to_string(&value)?)?;
+
+        Ok(())
+    }
+
+    /// Find the configuration file in the current directory.
+    pub(crate) fn find() -> Option<PathBuf> {
+        CONFIG_FILES
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.is_file())
+    }
+}
+
+/// Reorder the keys of the mappings in the value to follow the original value
+/// Keys the original has with empty values such as `false` or `[]` are kept, since they are left out
+/// of the value only because they are the defaults.
+fn order_like(value: Value, original: &Value) -> Value {
+    match (value, original) {
+        (Value::Mapping(mapping), Value::Mapping(original)) => {
+            let mut ordered = Mapping::new();
+            for (key, original_value) in original.iter() {
+                match mapping.get(key) {
+                    Some(value) => {
+                        ordered.insert(key.clone(), order_like(value.clone(), original_value));
+                    }
+                    None if is_empty(original_value) => {
+                        ordered.insert(key.clone(), original_value.clone());
+                    }
+                    None => {}
+                }
+            }
+            for (key, value) in mapping {
+                if !original.contains_key(&key) {
+                    ordered.insert(key, value);
+                }
+            }
+            Value::Mapping(ordered)
+        }
+        (Value::Sequence(sequence), Value::Sequence(original)) => Value::Sequence(
+            sequence
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| match original.get(i) {
+                    Some(original_value) => order_like(value, original_value),
+                    None => value,
+                })
+                .collect(),
+        ),
+        (value, _) => value,
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null | Value::Bool(false) => true,
+        Value::String(s) => s.is_empty(),
+        Value::Sequence(sequence) => sequence.is_empty(),
+        Value::Mapping(mapping) => mapping.is_empty(),
+        _ => false,
+    }
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -88,6 +189,8 @@ pub(crate) struct Format {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) hide_columns_without_values: Option<Vec<String>>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -108,116 +211,176 @@ pub(crate) struct ER {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) font: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct Lint {
-    pub(crate) require_table_comment: RequireTableComment,
-    pub(crate) require_column_comment: RequireColumnComment,
-    pub(crate) require_index_comment: RequireIndexComment,
-    pub(crate) require_constraint_comment: RequireConstraintComment,
-    pub(crate) require_trigger_comment: RequireTriggerComment,
-    pub(crate) unrelated_table: UnrelatedTable,
-    pub(crate) column_count: ColumnCount,
-    pub(crate) require_columns: RequireColumns,
-    pub(crate) duplicate_relations: DuplicateRelations,
-    pub(crate) require_foreign_key_index: RequireForeignKeyIndex,
-    pub(crate) label_style_big_query: LabelStyleBigQuery,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_table_comment: Option<RequireTableComment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_column_comment: Option<RequireColumnComment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_index_comment: Option<RequireIndexComment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_constraint_comment: Option<RequireConstraintComment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_trigger_comment: Option<RequireTriggerComment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) unrelated_table: Option<UnrelatedTable>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) column_count: Option<ColumnCount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_columns: Option<RequireColumns>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) duplicate_relations: Option<DuplicateRelations>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) require_foreign_key_index: Option<RequireForeignKeyIndex>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) label_style_big_query: Option<LabelStyleBigQuery>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireTableComment {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "is_false")]
     pub(crate) all_or_nothing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireColumnComment {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "is_false")]
     pub(crate) all_or_nothing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude_tables: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireIndexComment {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "is_false")]
     pub(crate) all_or_nothing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude_tables: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireConstraintComment {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "is_false")]
     pub(crate) all_or_nothing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude_tables: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireTriggerComment {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "is_false")]
     pub(crate) all_or_nothing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude_tables: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct UnrelatedTable {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "is_false")]
     pub(crate) all_or_nothing: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct ColumnCount {
     pub(crate) enabled: bool,
     pub(crate) max: i32,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireColumns {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) columns: Vec<RequireColumnsColumn>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireColumnsColumn {
     pub(crate) name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct DuplicateRelations {
     pub(crate) enabled: bool,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct RequireForeignKeyIndex {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub(crate) struct LabelStyleBigQuery {
     pub(crate) enabled: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) exclude: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -230,6 +393,8 @@ pub(crate) struct AdditionalRelation {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) def: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -239,24 +404,70 @@ pub(crate) struct AdditionalComment {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) table_comment: Option<String>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+    pub(crate) column_comments: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+    pub(crate) column_labels: BTreeMap<String, Vec<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+    pub(crate) index_comments: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+    pub(crate) constraint_comments: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(default)]
+    pub(crate) trigger_comments: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub(crate) labels: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
+}
+
+/// Subset of the tables documented as a separate ER diagram
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Viewpoint {
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub(crate) desc: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
-    pub(crate) column_comments: HashMap<String, String>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) labels: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
-    pub(crate) column_labels: HashMap<String, Vec<String>>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) tables: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
-    pub(crate) index_comments: HashMap<String, String>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) distance: Option<i32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
-    pub(crate) constraint_comments: HashMap<String, String>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub(crate) groups: Vec<ViewpointGroup>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ViewpointGroup {
+    pub(crate) name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
-    pub(crate) trigger_comments: HashMap<String, String>,
+    pub(crate) desc: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     pub(crate) labels: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub(crate) tables: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub(crate) color: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -271,6 +482,8 @@ pub(crate) struct Templates {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) puml: Option<Puml>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -282,6 +495,8 @@ pub(crate) struct Md {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) table: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -293,6 +508,8 @@ pub(crate) struct Dot {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) table: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -304,6 +521,8 @@ pub(crate) struct Puml {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) table: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -315,4 +534,86 @@ pub(crate) struct DetectVirtualRelations {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub(crate) strategy: Option<String>,
+    #[serde(flatten)]
+    pub(crate) extra: Mapping,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Save the configuration over the file of the content, and read the file back.
+    fn save_over(content: &str, edit: impl FnOnce(&mut Config)) -> String {
+        let path = std::env::temp_dir().join(format!(
+            "relations-config-{}-{}.yml",
+            std::process::id(),
+            content.len()
+        ));
+        fs::write(&path, content).unwrap();
+        let mut config = Config::load(&path).unwrap();
+        edit(&mut config);
+        config.save(&path).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        saved
+    }
+
+    #[test]
+    fn save_keeps_the_file() {
+        let content = r#"docPath: docs/schema
+unknown:
+  nested: 1
+dsn: mysql://root@localhost:3306/app
+lint:
+  unrelatedTable:
+    exclude: []
+    enabled: false
+    allOrNothing: false
+    unknown: true
+  requireTableComment:
+    enabled: true
+    allOrNothing: true
+    exclude:
+    - logs
+er:
+  format: svg
+  unknown:
+  - a
+  - b
+"#;
+        assert_eq!(save_over(content, |_| {}), content);
+    }
+
+    #[test]
+    fn save_appends_new_keys() {
+        let content = r#"unknown: 1
+dsn: mysql://root@localhost:3306/app
+lint:
+  unrelatedTable:
+    enabled: true
+"#;
+        let saved = save_over(content, |config| {
+            config.name = "app".to_string();
+            let lint = config.lint.as_mut().unwrap();
+            lint.unrelated_table.as_mut().unwrap().exclude = vec!["logs".to_string()];
+            lint.duplicate_relations = Some(DuplicateRelations {
+                enabled: true,
+                ..Default::default()
+            });
+        });
+        assert_eq!(
+            saved,
+            r#"unknown: 1
+dsn: mysql://root@localhost:3306/app
+lint:
+  unrelatedTable:
+    enabled: true
+    exclude:
+    - logs
+  duplicateRelations:
+    enabled: true
+name: app
+"#
+        );
+    }
 }
@@ -0,0 +1,192 @@
+use crate::configs::{AdditionalComment, AdditionalRelation, Config};
+use crate::databases::{glob_match, Table};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Entry of the configuration that no longer resolves against the schema
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct StaleEntry {
+    pub(crate) section: String,
+    pub(crate) entry: String,
+    pub(crate) reason: String,
+}
+
+impl StaleEntry {
+    fn new(section: &str, entry: String, reason: String) -> Self {
+        StaleEntry {
+            section: section.to_string(),
+            entry,
+            reason,
+        }
+    }
+}
+
+/// Find the relations, comments, include/exclude and lintExclude entries that do not resolve
+pub(crate) fn stale_entries(config: &Config, tables: &[Table]) -> Vec<StaleEntry> {
+    let mut entries = Vec::new();
+
+    for relation in config.relations.iter().flatten() {
+        if let Some(reason) = unresolved_relation(relation, tables) {
+            entries.push(StaleEntry::new(
+                "relations",
+                relation_entry(relation),
+                reason,
+            ));
+        }
+    }
+
+    for comment in config.comments.iter().flatten() {
+        let table = match find_table(tables, &comment.table) {
+            Some(table) => table,
+            None => {
+                entries.push(StaleEntry::new(
+                    "comments",
+                    comment.table.clone(),
+                    format!("table `{}` not found", comment.table),
+                ));
+                continue;
+            }
+        };
+        let stale_keys = [
+            (
+                "columnComments",
+                stale_keys(&comment.column_comments, |k| has_column(table, k)),
+            ),
+            (
+                "columnLabels",
+                stale_keys(&comment.column_labels, |k| has_column(table, k)),
+            ),
+            (
+                "indexComments",
+                stale_keys(&comment.index_comments, |k| has_index(table, k)),
+            ),
+            (
+                "constraintComments",
+                stale_keys(&comment.constraint_comments, |k| has_constraint(table, k)),
+            ),
+        ];
+        for (key, names) in stale_keys {
+            for name in names {
+                entries.push(StaleEntry::new(
+                    "comments",
+                    format!("{}.{}.{}", comment.table, key, name),
+                    format!("`{}` not found in table `{}`", name, comment.table),
+                ));
+            }
+        }
+    }
+
+    for (section, patterns) in [
+        ("include", &config.include),
+        ("exclude", &config.exclude),
+        ("lintExclude", &config.lint_exclude),
+    ] {
+        for pattern in patterns.iter().filter(|p| !matches_any_table(p, tables)) {
+            entries.push(StaleEntry::new(
+                section,
+                pattern.clone(),
+                "no table matches the pattern".to_string(),
+            ));
+        }
+    }
+
+    entries
+}
+
+/// Remove the entries reported by `stale_entries` from the configuration.
+pub(crate) fn remove_stale_entries(config: &mut Config, tables: &[Table]) {
+    if let Some(relations) = config.relations.as_mut() {
+        relations.retain(|r| unresolved_relation(r, tables).is_none());
+    }
+
+    if let Some(comments) = config.comments.as_mut() {
+        comments.retain(|c| find_table(tables, &c.table).is_some());
+        for comment in comments.iter_mut() {
+            remove_stale_keys(comment, tables);
+        }
+    }
+
+    for patterns in [
+        &mut config.include,
+        &mut config.exclude,
+        &mut config.lint_exclude,
+    ] {
+        patterns.retain(|p| matches_any_table(p, tables));
+    }
+}
+
+fn remove_stale_keys(comment: &mut AdditionalComment, tables: &[Table]) {
+    let table = match find_table(tables, &comment.table) {
+        Some(table) => table,
+        None => return,
+    };
+    comment.column_comments.retain(|k, _| has_column(table, k));
+    comment.column_labels.retain(|k, _| has_column(table, k));
+    comment.index_comments.retain(|k, _| has_index(table, k));
+    comment
+        .constraint_comments
+        .retain(|k, _| has_constraint(table, k));
+}
+
+/// Reason the relation does not resolve, or `None` if every table and column exists
+fn unresolved_relation(relation: &AdditionalRelation, tables: &[Table]) -> Option<String> {
+    let sides = [
+        (&relation.table, &relation.columns),
+        (&relation.parent_table, &relation.parent_columns),
+    ];
+    for (table_name, column_names) in sides {
+        let table = match find_table(tables, table_name) {
+            Some(table) => table,
+            None => return Some(format!("table `{}` not found", table_name)),
+        };
+        if let Some(column_name) = column_names.iter().find(|c| !has_column(table, c)) {
+            return Some(format!(
+                "column `{}` not found in table `{}`",
+                column_name, table_name
+            ));
+        }
+    }
+
+    None
+}
+
+fn relation_entry(relation: &AdditionalRelation) -> String {
+    format!(
+        "{}({}) -> {}({})",
+        relation.table,
+        relation.columns.join(", "),
+        relation.parent_table,
+        relation.parent_columns.join(", ")
+    )
+}
+
+/// Find the table by `table` or `database.table`
+fn find_table<'a>(tables: &'a [Table], name: &str) -> Option<&'a Table> {
+    tables
+        .iter()
+        .find(|t| t.name == name || format!("{}.{}", t.database, t.name) == name)
+}
+
+fn matches_any_table(pattern: &str, tables: &[Table]) -> bool {
+    tables.iter().any(|t| {
+        glob_match(pattern, &t.name) || glob_match(pattern, &format!("{}.{}", t.database, t.name))
+    })
+}
+
+fn has_column(table: &Table, name: &str) -> bool {
+    table.columns.iter().any(|c| c.name == name)
+}
+
+fn has_index(table: &Table, name: &str) -> bool {
+    table.indexes.iter().any(|i| i.name == name)
+}
+
+/// Constraints are primary keys, unique keys and foreign keys
+fn has_constraint(table: &Table, name: &str) -> bool {
+    table.indexes.iter().any(|i| i.is_unique && i.name == name)
+        || table.foreign_keys.iter().any(|f| f.name == name)
+}
+
+fn stale_keys<V>(map: &BTreeMap<String, V>, exists: impl Fn(&str) -> bool) -> Vec<&String> {
+    map.keys().filter(|k| !exists(k)).collect()
+}
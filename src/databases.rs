@@ -1,4 +1,12 @@
+mod data_type;
 mod database;
+mod filter;
+mod graph;
 pub(crate) mod mysql;
+#[cfg(test)]
+pub(crate) mod testing;
 
+pub(crate) use data_type::*;
 pub(crate) use database::*;
+pub(crate) use filter::*;
+pub(crate) use graph::*;
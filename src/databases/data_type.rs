@@ -0,0 +1,231 @@
+use std::str::FromStr;
+
+/// Group of MySQL data types whose values can be compared with each other
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) enum DataTypeFamily {
+    Integer,
+    Decimal,
+    Float,
+    String,
+    Binary,
+    Date,
+    DateTime,
+    Time,
+    Year,
+    Other,
+}
+
+/// How strictly the data types of a parent column and a child column must match
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) enum DataTypeCompatibility {
+    /// Same base type, signedness, length, character set and collation. Display widths of integers
+    /// are ignored.
+    Strict,
+    /// Same family such as `int` and `bigint`, or `char(36)` and `varchar(36)`
+    Lenient,
+}
+
+impl FromStr for DataTypeCompatibility {
+    type Err = Box<dyn std::error::Error>;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "strict" => Ok(DataTypeCompatibility::Strict),
+            "lenient" => Ok(DataTypeCompatibility::Lenient),
+            _ => Err(Self::Err::from(format!(
+                "unsupported compatibility: `{}`",
+                s
+            ))),
+        }
+    }
+}
+
+/// MySQL data type such as `bigint(20) unsigned` or `varchar(36)`
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub(crate) struct DataType {
+    /// Lowercase type name with aliases resolved, such as `int` for `integer`
+    pub(crate) base: String,
+    /// Display width of integers, or length of strings and precision of decimals
+    pub(crate) width: Option<u32>,
+    pub(crate) scale: Option<u32>,
+    pub(crate) is_unsigned: bool,
+    pub(crate) character_set: Option<String>,
+    pub(crate) collation: Option<String>,
+}
+
+impl DataType {
+    /// Parse the type of `DESCRIBE` such as `decimal(10,2) unsigned zerofill`.
+    pub(crate) fn parse(s: &str) -> DataType {
+        let s = s.trim().to_lowercase();
+        let (name, rest) = match s.find(|c: char| c == '(' || c.is_whitespace()) {
+            Some(i) => s.split_at(i),
+            None => (s.as_str(), ""),
+        };
+        let (arguments, modifiers) = match rest.strip_prefix('(').and_then(|r| r.split_once(')')) {
+            Some((arguments, modifiers)) => (Some(arguments), modifiers),
+            None => (None, rest),
+        };
+        let numbers = arguments
+            .map(|a| {
+                a.split(',')
+                    .map(|n| n.trim().parse::<u32>().ok())
+                    .collect::<Vec<Option<u32>>>()
+            })
+            .unwrap_or_default();
+        let base = match name {
+            "integer" => "int",
+            "bool" | "boolean" => "tinyint",
+            "dec" | "numeric" | "fixed" => "decimal",
+            "real" => "double",
+            name => name,
+        };
+
+        DataType {
+            base: base.to_string(),
+            width: numbers.first().copied().flatten(),
+            scale: numbers.get(1).copied().flatten(),
+            is_unsigned: modifiers.split_whitespace().any(|m| m == "unsigned"),
+            character_set: None,
+            collation: None,
+        }
+    }
+
+    pub(crate) fn family(&self) -> DataTypeFamily {
+        match self.base.as_str() {
+            "tinyint" | "smallint" | "mediumint" | "int" | "bigint" => DataTypeFamily::Integer,
+            "decimal" => DataTypeFamily::Decimal,
+            "float" | "double" => DataTypeFamily::Float,
+            "char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum"
+            | "set" => DataTypeFamily::String,
+            "binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" | "bit" => {
+                DataTypeFamily::Binary
+            }
+            "date" => DataTypeFamily::Date,
+            "datetime" | "timestamp" => DataTypeFamily::DateTime,
+            "time" => DataTypeFamily::Time,
+            "year" => DataTypeFamily::Year,
+            _ => DataTypeFamily::Other,
+        }
+    }
+
+    /// Whether values of the data types can be joined on under the compatibility
+    pub(crate) fn is_compatible(
+        &self,
+        other: &DataType,
+        compatibility: DataTypeCompatibility,
+    ) -> bool {
+        match compatibility {
+            DataTypeCompatibility::Strict => {
+                let is_same_width = match self.family() {
+                    // Display widths are deprecated and do not change the range
+                    DataTypeFamily::Integer => true,
+                    _ => self.width == other.width && self.scale == other.scale,
+                };
+                self.base == other.base
+                    && self.is_unsigned == other.is_unsigned
+                    && is_same_width
+                    && self.character_set == other.character_set
+                    && self.collation == other.collation
+            }
+            DataTypeCompatibility::Lenient => {
+                self.family() == other.family() && self.family() != DataTypeFamily::Other
+                    || self.base == other.base
+            }
+        }
+    }
+
+    /// Whether the data types are integers of which only one is unsigned
+    pub(crate) fn is_signedness_mismatch(&self, other: &DataType) -> bool {
+        self.family() == DataTypeFamily::Integer
+            && other.family() == DataTypeFamily::Integer
+            && self.is_unsigned != other.is_unsigned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_charset(s: &str, character_set: &str, collation: &str) -> DataType {
+        DataType {
+            character_set: Some(character_set.to_string()),
+            collation: Some(collation.to_string()),
+            ..DataType::parse(s)
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let data_type = DataType::parse("decimal(10,2) unsigned zerofill");
+        assert_eq!(data_type.base, "decimal");
+        assert_eq!(data_type.width, Some(10));
+        assert_eq!(data_type.scale, Some(2));
+        assert!(data_type.is_unsigned);
+        assert_eq!(DataType::parse("INTEGER").base, "int");
+        assert_eq!(DataType::parse("bigint(20) unsigned").width, Some(20));
+    }
+
+    #[test]
+    fn integer_display_width() {
+        let int11 = DataType::parse("int(11)");
+        let int = DataType::parse("int");
+        assert!(int11.is_compatible(&int, DataTypeCompatibility::Strict));
+        assert!(int11.is_compatible(&int, DataTypeCompatibility::Lenient));
+    }
+
+    #[test]
+    fn unsigned_integer_display_width() {
+        let unsigned = DataType::parse("bigint unsigned");
+        let unsigned20 = DataType::parse("bigint(20) unsigned");
+        let signed20 = DataType::parse("bigint(20)");
+        assert!(unsigned.is_compatible(&unsigned20, DataTypeCompatibility::Strict));
+        assert!(!unsigned20.is_compatible(&signed20, DataTypeCompatibility::Strict));
+        assert!(unsigned20.is_compatible(&signed20, DataTypeCompatibility::Lenient));
+        assert!(unsigned20.is_signedness_mismatch(&signed20));
+        assert!(!unsigned.is_signedness_mismatch(&unsigned20));
+    }
+
+    #[test]
+    fn char_and_varchar() {
+        let varchar = DataType::parse("varchar(36)");
+        let char = DataType::parse("char(36)");
+        assert!(!varchar.is_compatible(&char, DataTypeCompatibility::Strict));
+        assert!(varchar.is_compatible(&char, DataTypeCompatibility::Lenient));
+        assert!(!varchar.is_signedness_mismatch(&char));
+    }
+
+    #[test]
+    fn character_set_and_collation() {
+        let utf8mb4 = with_charset("varchar(36)", "utf8mb4", "utf8mb4_general_ci");
+        let bin = with_charset("varchar(36)", "utf8mb4", "utf8mb4_bin");
+        let latin1 = with_charset("varchar(36)", "latin1", "utf8mb4_general_ci");
+        assert!(utf8mb4.is_compatible(&utf8mb4.clone(), DataTypeCompatibility::Strict));
+        assert!(!utf8mb4.is_compatible(&bin, DataTypeCompatibility::Strict));
+        assert!(!utf8mb4.is_compatible(&latin1, DataTypeCompatibility::Strict));
+        assert!(utf8mb4.is_compatible(&latin1, DataTypeCompatibility::Lenient));
+    }
+
+    #[test]
+    fn different_families() {
+        let int = DataType::parse("int");
+        let varchar = DataType::parse("varchar(11)");
+        assert!(!int.is_compatible(&varchar, DataTypeCompatibility::Lenient));
+        assert!(DataType::parse("json")
+            .is_compatible(&DataType::parse("json"), DataTypeCompatibility::Lenient));
+        assert!(!DataType::parse("json")
+            .is_compatible(&DataType::parse("geometry"), DataTypeCompatibility::Lenient));
+    }
+
+    #[test]
+    fn temporal_types() {
+        let datetime = DataType::parse("datetime");
+        assert!(datetime.is_compatible(
+            &DataType::parse("timestamp"),
+            DataTypeCompatibility::Lenient
+        ));
+        assert!(!datetime.is_compatible(&DataType::parse("date"), DataTypeCompatibility::Lenient));
+        assert!(!DataType::parse("date")
+            .is_compatible(&DataType::parse("time"), DataTypeCompatibility::Lenient));
+        assert!(!DataType::parse("year")
+            .is_compatible(&DataType::parse("date"), DataTypeCompatibility::Lenient));
+    }
+}
@@ -1,4 +1,7 @@
+use crate::databases::{glob_match, DataType, DataTypeCompatibility, TableFilter};
+use anyhow::{anyhow, Result};
 use itertools::Itertools;
+use regex::Regex;
 use std::collections::HashSet;
 
 #[allow(unused)]
@@ -8,6 +11,8 @@ pub(crate) struct Table {
     pub(crate) database: String,
     pub(crate) columns: Vec<Column>,
     pub(crate) indexes: Vec<Index>,
+    pub(crate) foreign_keys: Vec<ForeignKey>,
+    pub(crate) comment: Option<String>,
 }
 
 #[allow(unused)]
@@ -16,6 +21,19 @@ pub(crate) struct Column {
     pub(crate) name: String,
     pub(crate) data_type: String,
     pub(crate) is_auto_increment: bool,
+    pub(crate) comment: Option<String>,
+    pub(crate) character_set: Option<String>,
+    pub(crate) collation: Option<String>,
+}
+
+impl Column {
+    /// Data type parsed from the type of `DESCRIBE`, with the character set and the collation
+    pub(crate) fn parsed_data_type(&self) -> DataType {
+        let mut data_type = DataType::parse(&self.data_type);
+        data_type.character_set = self.character_set.clone();
+        data_type.collation = self.collation.clone();
+        data_type
+    }
 }
 
 #[allow(unused)]
@@ -24,6 +42,18 @@ pub(crate) struct Index {
     pub(crate) name: String,
     pub(crate) columns: Vec<Column>,
     pub(crate) is_unique: bool,
+    pub(crate) comment: Option<String>,
+}
+
+/// Foreign key constraint declared in the database
+#[allow(unused)]
+#[derive(Debug, Clone)]
+pub(crate) struct ForeignKey {
+    pub(crate) name: String,
+    pub(crate) columns: Vec<Column>,
+    pub(crate) parent_database: String,
+    pub(crate) parent_table: String,
+    pub(crate) parent_columns: Vec<String>,
 }
 
 #[allow(unused)]
@@ -33,9 +63,80 @@ pub(crate) struct Relation {
     pub(crate) columns: Vec<Column>,
     pub(crate) parent_table: Table,
     pub(crate) parent_columns: Vec<Column>,
+    /// Whether the relation is not declared as a foreign key in the database
+    pub(crate) is_virtual: bool,
+}
+
+impl Relation {
+    /// Definition of the relation such as `posts(user_id) -> users(id)`
+    pub(crate) fn definition(&self) -> String {
+        format!(
+            "{}({}) -> {}({})",
+            self.table.name,
+            self.columns.iter().map(|c| &c.name).join(", "),
+            self.parent_table.name,
+            self.parent_columns.iter().map(|c| &c.name).join(", ")
+        )
+    }
+
+    /// Whether the relations connect the same columns of the same tables
+    pub(crate) fn is_same(&self, other: &Relation) -> bool {
+        self.table.name == other.table.name
+            && self.parent_table.name == other.parent_table.name
+            && self
+                .columns
+                .iter()
+                .map(|c| &c.name)
+                .eq(other.columns.iter().map(|c| &c.name))
+            && self
+                .parent_columns
+                .iter()
+                .map(|c| &c.name)
+                .eq(other.parent_columns.iter().map(|c| &c.name))
+    }
+}
+
+impl Table {
+    /// Primary key of the table
+    pub(crate) fn primary_key(&self) -> Option<&Index> {
+        self.indexes.iter().find(|index| index.name == "PRIMARY")
+    }
+
+    /// Whether the columns are a left prefix of any index, that is, lookups by them can use the index
+    pub(crate) fn has_index_on(&self, columns: &[Column]) -> bool {
+        self.indexes.iter().any(|index| {
+            index.columns.len() >= columns.len()
+                && columns.iter().all(|column| {
+                    index.columns[..columns.len()]
+                        .iter()
+                        .any(|c| c.name == column.name)
+                })
+        })
+    }
+
+    /// Whether the columns are unique in the table, that is, a unique index consists of them
+    pub(crate) fn is_unique_columns(&self, columns: &[Column]) -> bool {
+        self.indexes.iter().any(|index| {
+            index.is_unique
+                && index.columns.len() == columns.len()
+                && index
+                    .columns
+                    .iter()
+                    .all(|c| columns.iter().any(|column| column.name == c.name))
+        })
+    }
+}
+
+/// Relation declared explicitly by table and column names
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RelationDefinition {
+    pub(crate) table: String,
+    pub(crate) columns: Vec<String>,
+    pub(crate) parent_table: String,
+    pub(crate) parent_columns: Vec<String>,
 }
 
-pub(crate) fn relations(tables: Vec<Table>, rules: Vec<Rule>) -> Vec<Relation> {
+pub(crate) fn relations(tables: Vec<Table>, rules: &[Rule]) -> Vec<Relation> {
     let mut relations = Vec::new();
     for parent_table in tables.iter() {
         for index in parent_table.indexes.iter() {
@@ -60,7 +161,7 @@ pub(crate) fn relations(tables: Vec<Table>, rules: Vec<Rule>) -> Vec<Relation> {
                                                     child_column,
                                                 )
                                             })
-                                            .then(|| ())
+                                            .then_some(())
                                     })
                                     .is_some()
                             });
@@ -70,6 +171,7 @@ pub(crate) fn relations(tables: Vec<Table>, rules: Vec<Rule>) -> Vec<Relation> {
                             columns: child_columns.into_iter().cloned().collect(),
                             parent_table: parent_table.clone(),
                             parent_columns: index.columns.clone(),
+                            is_virtual: true,
                         });
                     }
                 }
@@ -80,6 +182,178 @@ pub(crate) fn relations(tables: Vec<Table>, rules: Vec<Rule>) -> Vec<Relation> {
     relations
 }
 
+/// Relations declared as foreign keys in the database
+/// Foreign keys referencing tables that are not introspected are ignored.
+pub(crate) fn declared_relations(tables: &[Table]) -> Vec<Relation> {
+    let mut relations = Vec::new();
+    for table in tables.iter() {
+        for foreign_key in table.foreign_keys.iter() {
+            // Foreign keys to tables of other databases are related only when those are introspected
+            let parent_table = match tables.iter().find(|t| {
+                t.database == foreign_key.parent_database && t.name == foreign_key.parent_table
+            }) {
+                Some(parent_table) => parent_table,
+                None => continue,
+            };
+            let parent_columns = foreign_key
+                .parent_columns
+                .iter()
+                .filter_map(|name| parent_table.columns.iter().find(|c| &c.name == name))
+                .cloned()
+                .collect::<Vec<Column>>();
+            if parent_columns.len() != foreign_key.parent_columns.len() {
+                continue;
+            }
+            relations.push(Relation {
+                table: table.clone(),
+                columns: foreign_key.columns.clone(),
+                parent_table: parent_table.clone(),
+                parent_columns,
+                is_virtual: false,
+            });
+        }
+    }
+
+    relations
+}
+
+/// Relations without duplicates, keeping the first of the same relations
+pub(crate) fn dedup_relations(relations: Vec<Relation>) -> Vec<Relation> {
+    let mut deduped: Vec<Relation> = Vec::new();
+    for relation in relations {
+        if !deduped.iter().any(|r| r.is_same(&relation)) {
+            deduped.push(relation);
+        }
+    }
+
+    deduped
+}
+
+/// Relations annotated in column comments such as `ref: users.id`
+/// The pattern must capture the parent table and column as the `table` and `column` named groups.
+/// Annotations referencing tables or columns that are not introspected are ignored.
+pub(crate) fn comment_hint_relations(tables: &[Table], pattern: &Regex) -> Vec<Relation> {
+    let mut relations = Vec::new();
+    for table in tables.iter() {
+        for column in table.columns.iter() {
+            let comment = match &column.comment {
+                Some(comment) => comment,
+                None => continue,
+            };
+            for captures in pattern.captures_iter(comment) {
+                let (parent_table_name, parent_column_name) =
+                    match (captures.name("table"), captures.name("column")) {
+                        (Some(table), Some(column)) => (table.as_str(), column.as_str()),
+                        _ => continue,
+                    };
+                let parent_table = match tables.iter().find(|t| t.name == parent_table_name) {
+                    Some(parent_table) => parent_table,
+                    None => continue,
+                };
+                let parent_column = match parent_table
+                    .columns
+                    .iter()
+                    .find(|c| c.name == parent_column_name)
+                {
+                    Some(parent_column) => parent_column,
+                    None => continue,
+                };
+                relations.push(Relation {
+                    table: table.clone(),
+                    columns: vec![column.clone()],
+                    parent_table: parent_table.clone(),
+                    parent_columns: vec![parent_column.clone()],
+                    is_virtual: true,
+                });
+            }
+        }
+    }
+
+    relations
+}
+
+/// Resolve the explicitly declared relations against the tables.
+/// Tables are named `table` or `database.table`, and unqualified parent tables are looked up in the
+/// database of the child table first. Relations naming tables that are not introspected, such as
+/// excluded ones, are skipped.
+pub(crate) fn forced_relations(
+    tables: &[Table],
+    definitions: &[RelationDefinition],
+) -> Result<Vec<Relation>> {
+    let find_columns = |table: &Table, names: &[String]| {
+        names
+            .iter()
+            .map(|name| {
+                table
+                    .columns
+                    .iter()
+                    .find(|column| &column.name == name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("column not found: `{}.{}`", table.name, name))
+            })
+            .collect::<Result<Vec<Column>>>()
+    };
+    let find_table = |name: &str, database: Option<&str>| -> Result<Option<&Table>> {
+        let candidates = match name.split_once('.') {
+            Some((database, name)) => tables
+                .iter()
+                .filter(|table| table.database == database && table.name == name)
+                .collect::<Vec<&Table>>(),
+            None => {
+                let candidates = tables
+                    .iter()
+                    .filter(|table| table.name == name)
+                    .collect::<Vec<&Table>>();
+                match database {
+                    Some(database) if candidates.iter().any(|t| t.database == database) => {
+                        candidates
+                            .into_iter()
+                            .filter(|t| t.database == database)
+                            .collect()
+                    }
+                    _ => candidates,
+                }
+            }
+        };
+        match candidates.as_slice() {
+            [] => Ok(None),
+            [table] => Ok(Some(table)),
+            _ => Err(anyhow!("ambiguous table: `{}`", name)),
+        }
+    };
+
+    let mut relations = Vec::new();
+    for definition in definitions.iter() {
+        let table = find_table(&definition.table, None)?;
+        let parent_table = match table {
+            Some(table) => find_table(&definition.parent_table, Some(&table.database))?,
+            None => None,
+        };
+        let (table, parent_table) = match (table, parent_table) {
+            (Some(table), Some(parent_table)) => (table, parent_table),
+            _ => {
+                log::warn!(
+                    "skipped relation `{}.{} -> {}.{}`: table not found",
+                    definition.table,
+                    definition.columns.join(","),
+                    definition.parent_table,
+                    definition.parent_columns.join(",")
+                );
+                continue;
+            }
+        };
+        relations.push(Relation {
+            table: table.clone(),
+            columns: find_columns(table, &definition.columns)?,
+            parent_table: parent_table.clone(),
+            parent_columns: find_columns(parent_table, &definition.parent_columns)?,
+            is_virtual: true,
+        });
+    }
+
+    Ok(relations)
+}
+
 pub(crate) type Rule = Box<dyn Fn(&Table, &Column, &Table, &Column) -> bool>;
 
 pub(crate) fn rule_ends_with_excepting_the_prefixes(prefixes: HashSet<String>) -> Rule {
@@ -105,3 +379,102 @@ pub(crate) fn rule_ends_with() -> Rule {
 pub(crate) fn rule_same_data_type() -> Rule {
     Box::new(|_, parent_column, _, child_column| parent_column.data_type == child_column.data_type)
 }
+
+pub(crate) fn rule_compatible_data_type(compatibility: DataTypeCompatibility) -> Rule {
+    Box::new(move |_, parent_column, _, child_column| {
+        parent_column
+            .parsed_data_type()
+            .is_compatible(&child_column.parsed_data_type(), compatibility)
+    })
+}
+
+pub(crate) fn rule_table_filters(parent_filter: TableFilter, child_filter: TableFilter) -> Rule {
+    Box::new(move |parent_table, _, child_table, _| {
+        parent_filter.matches(&parent_table.database, &parent_table.name)
+            && child_filter.matches(&child_table.database, &child_table.name)
+    })
+}
+
+pub(crate) fn rule_ignore_columns(patterns: Vec<String>) -> Rule {
+    Box::new(move |_, _, child_table, child_column| {
+        let qualified_name = format!("{}.{}", child_table.name, child_column.name);
+        !patterns.iter().any(|pattern| {
+            glob_match(pattern, &child_column.name) || glob_match(pattern, &qualified_name)
+        })
+    })
+}
+
+pub(crate) fn rule_forbidden_relations(pairs: Vec<(String, String)>) -> Rule {
+    Box::new(move |parent_table, _, child_table, _| {
+        !pairs.iter().any(|(child_pattern, parent_pattern)| {
+            glob_match(child_pattern, &child_table.name)
+                && glob_match(parent_pattern, &parent_table.name)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::table;
+
+    fn definition(table: &str, parent_table: &str) -> RelationDefinition {
+        RelationDefinition {
+            table: table.to_string(),
+            columns: vec!["user_id".to_string()],
+            parent_table: parent_table.to_string(),
+            parent_columns: vec!["id".to_string()],
+        }
+    }
+
+    fn in_database(mut table: Table, database: &str) -> Table {
+        table.database = database.to_string();
+        table
+    }
+
+    #[test]
+    fn forced_relations_by_database() {
+        let tables = vec![
+            table("posts", &["id", "user_id"]),
+            table("users", &["id"]),
+            in_database(table("users", &["id"]), "auth"),
+        ];
+        let relations = forced_relations(
+            &tables,
+            &[
+                definition("posts", "users"),
+                definition("posts", "auth.users"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(relations[0].parent_table.database, "app");
+        assert_eq!(relations[1].parent_table.database, "auth");
+
+        let tables = vec![
+            table("posts", &["id", "user_id"]),
+            in_database(table("users", &["id"]), "auth"),
+            in_database(table("users", &["id"]), "legacy"),
+        ];
+        assert!(forced_relations(&tables, &[definition("posts", "users")]).is_err());
+    }
+
+    #[test]
+    fn forced_relations_of_missing_tables() {
+        let tables = vec![table("posts", &["id", "user_id"]), table("users", &["id"])];
+        let relations = forced_relations(
+            &tables,
+            &[
+                definition("posts", "users"),
+                definition("posts", "archived_users"),
+                definition("archived_posts", "users"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(relations.len(), 1);
+        assert_eq!(relations[0].definition(), "posts(user_id) -> users(id)");
+
+        let mut missing_column = definition("posts", "users");
+        missing_column.columns = vec!["author_id".to_string()];
+        assert!(forced_relations(&tables, &[missing_column]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+/// Glob patterns selecting tables by `database.table` or `table`
+/// `*` matches any sequence of characters and `?` matches any single character.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TableFilter {
+    pub(crate) include: Vec<String>,
+    pub(crate) exclude: Vec<String>,
+}
+
+impl TableFilter {
+    pub(crate) fn new(include: Vec<String>, exclude: Vec<String>) -> Self {
+        TableFilter { include, exclude }
+    }
+
+    /// A table is selected if it matches any include pattern (or no include pattern is given)
+    /// and does not match any exclude pattern.
+    pub(crate) fn matches(&self, database_name: &str, table_name: &str) -> bool {
+        let qualified_name = format!("{}.{}", database_name, table_name);
+        let is_match = |pattern: &String| {
+            glob_match(pattern, table_name) || glob_match(pattern, &qualified_name)
+        };
+
+        (self.include.is_empty() || self.include.iter().any(is_match))
+            && !self.exclude.iter().any(is_match)
+    }
+}
+
+/// Match the string with the glob pattern
+pub(crate) fn glob_match(pattern: &str, s: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let s = s.chars().collect::<Vec<char>>();
+
+    // Position of the last `*` in the pattern and the position in the string it has consumed up to
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut i) = (0, 0);
+    while i < s.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, i));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == s[i] => {
+                p += 1;
+                i += 1;
+            }
+            _ => match backtrack {
+                Some((star, consumed)) => {
+                    backtrack = Some((star, consumed + 1));
+                    p = star + 1;
+                    i = consumed + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_match_literal() {
+        assert!(glob_match("users", "users"));
+        assert!(!glob_match("users", "user"));
+        assert!(!glob_match("user", "users"));
+    }
+
+    #[test]
+    fn glob_match_star() {
+        assert!(glob_match("*_archive", "posts_archive"));
+        assert!(glob_match("*_archive", "_archive"));
+        assert!(!glob_match("*_archive", "posts_archived"));
+        assert!(glob_match("tmp_*_old", "tmp_posts_2022_old"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("**", "users"));
+    }
+
+    #[test]
+    fn glob_match_trailing_star() {
+        assert!(glob_match("tmp_*", "tmp_"));
+        assert!(glob_match("tmp_*", "tmp_posts"));
+        assert!(!glob_match("tmp_*", "tmp"));
+    }
+
+    #[test]
+    fn glob_match_question_mark() {
+        assert!(glob_match("log_?", "log_1"));
+        assert!(!glob_match("log_?", "log_"));
+        assert!(!glob_match("log_?", "log_10"));
+        assert!(glob_match("log_??*", "log_10"));
+    }
+
+    #[test]
+    fn glob_match_empty() {
+        assert!(glob_match("", ""));
+        assert!(!glob_match("", "users"));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn glob_match_qualified() {
+        assert!(glob_match("app.users", "app.users"));
+        assert!(glob_match("app.*", "app.users"));
+        assert!(!glob_match("app.*", "other.users"));
+        assert!(glob_match("*.users", "other.users"));
+    }
+
+    #[test]
+    fn table_filter_matches() {
+        let filter = TableFilter::new(
+            vec!["app.*".to_string()],
+            vec!["*_archive".to_string(), "app.tmp".to_string()],
+        );
+        assert!(filter.matches("app", "users"));
+        assert!(!filter.matches("other", "users"));
+        assert!(!filter.matches("app", "posts_archive"));
+        assert!(!filter.matches("app", "tmp"));
+
+        let filter = TableFilter::default();
+        assert!(filter.matches("app", "users"));
+    }
+}
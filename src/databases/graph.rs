@@ -0,0 +1,533 @@
+use crate::databases::{Relation, Table};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Graph of tables connected by relations
+/// Edges are directed from child tables to parent tables. Tables are identified by name.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Graph {
+    pub(crate) nodes: BTreeSet<String>,
+    pub(crate) edges: Vec<(String, String)>,
+}
+
+impl Graph {
+    pub(crate) fn new(tables: &[Table], relations: &[Relation]) -> Self {
+        let nodes = tables
+            .iter()
+            .map(|table| table.name.clone())
+            .chain(
+                relations
+                    .iter()
+                    .flat_map(|r| [r.table.name.clone(), r.parent_table.name.clone()]),
+            )
+            .collect();
+        let edges = relations
+            .iter()
+            .map(|r| (r.table.name.clone(), r.parent_table.name.clone()))
+            .collect();
+
+        Graph { nodes, edges }
+    }
+
+    /// Tables adjacent to the table regardless of the direction of the edges
+    pub(crate) fn neighbors(&self, name: &str) -> BTreeSet<&String> {
+        self.edges
+            .iter()
+            .filter_map(|(child, parent)| {
+                if child == name {
+                    Some(parent)
+                } else if parent == name {
+                    Some(child)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Tables reachable from the table within the distance regardless of the direction of the edges
+    pub(crate) fn neighborhood(&self, name: &str, distance: usize) -> BTreeSet<String> {
+        let mut distances = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        if self.nodes.contains(name) {
+            distances.insert(name.to_string(), 0);
+            queue.push_back(name.to_string());
+        }
+        while let Some(node) = queue.pop_front() {
+            let d = distances[&node];
+            if d >= distance {
+                continue;
+            }
+            for neighbor in self.neighbors(&node) {
+                if !distances.contains_key(neighbor) {
+                    distances.insert(neighbor.clone(), d + 1);
+                    queue.push_back(neighbor.clone());
+                }
+            }
+        }
+
+        distances.into_keys().collect()
+    }
+
+    /// Sets of tables connected by relations regardless of the direction of the edges
+    pub(crate) fn components(&self) -> Vec<BTreeSet<String>> {
+        let mut components: Vec<BTreeSet<String>> = Vec::new();
+        for node in self.nodes.iter() {
+            if !components.iter().any(|c| c.contains(node)) {
+                components.push(self.neighborhood(node, usize::MAX));
+            }
+        }
+
+        components
+    }
+
+    /// Number of edges whose parent is the table
+    pub(crate) fn in_degree(&self, name: &str) -> usize {
+        self.edges
+            .iter()
+            .filter(|(_, parent)| parent == name)
+            .count()
+    }
+
+    /// Tables that are parents of the table
+    pub(crate) fn parents(&self, name: &str) -> BTreeSet<&String> {
+        self.edges
+            .iter()
+            .filter(|(child, _)| child == name)
+            .map(|(_, parent)| parent)
+            .collect()
+    }
+
+    /// Tables with no relations
+    pub(crate) fn orphans(&self) -> Vec<&String> {
+        self.nodes
+            .iter()
+            .filter(|node| !self.edges.iter().any(|(c, p)| c == *node || p == *node))
+            .collect()
+    }
+
+    /// Sets of tables that reach each other by following the edges
+    /// A table referencing itself forms a set of its own.
+    pub(crate) fn strongly_connected_components(&self) -> Vec<BTreeSet<String>> {
+        let adjacency = self.adjacency();
+        let mut tarjan = Tarjan {
+            adjacency: &adjacency,
+            indexes: BTreeMap::new(),
+            lowlinks: BTreeMap::new(),
+            stack: Vec::new(),
+            on_stack: BTreeSet::new(),
+            components: Vec::new(),
+        };
+        for node in self.nodes.iter() {
+            if !tarjan.indexes.contains_key(node) {
+                tarjan.visit(node);
+            }
+        }
+
+        tarjan
+            .components
+            .into_iter()
+            .filter(|component| {
+                let first = component.iter().next().unwrap();
+                component.len() > 1 || adjacency[first].contains(first)
+            })
+            .sorted()
+            .collect()
+    }
+
+    /// A cycle in each strongly connected component, starting and ending at the first table
+    pub(crate) fn cycles(&self) -> Vec<Vec<String>> {
+        self.strongly_connected_components()
+            .into_iter()
+            .map(|component| {
+                let start = component.iter().next().unwrap().clone();
+                let mut path = vec![start.clone()];
+                self.find_path_back(&start, &component, &mut path);
+                path
+            })
+            .collect()
+    }
+
+    /// Longest chains of parents, ignoring the edges within cycles
+    /// Only maximal chains are kept, so a chain that is the tail of a longer chain is dropped.
+    /// Chains are sorted by length in descending order.
+    pub(crate) fn longest_chains(&self) -> Vec<Vec<String>> {
+        let adjacency = self.adjacency();
+        let cyclic = self.strongly_connected_components();
+        let is_acyclic_edge = |child: &String, parent: &String| {
+            !cyclic
+                .iter()
+                .any(|c| c.contains(child) && c.contains(parent))
+        };
+        let mut memo: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for node in self.nodes.iter() {
+            longest_chain_from(&adjacency, node, &is_acyclic_edge, &mut memo);
+        }
+        let chains = memo.into_values().collect::<Vec<_>>();
+
+        chains
+            .iter()
+            .filter(|chain| chain.len() > 1)
+            .filter(|chain| {
+                !chains
+                    .iter()
+                    .any(|other| other.len() > chain.len() && other.ends_with(chain))
+            })
+            .cloned()
+            .sorted_by(|a, b| b.len().cmp(&a.len()).then(a.cmp(b)))
+            .collect()
+    }
+
+    /// Parents of each table
+    fn adjacency(&self) -> BTreeMap<&String, BTreeSet<&String>> {
+        let mut adjacency = self
+            .nodes
+            .iter()
+            .map(|node| (node, BTreeSet::new()))
+            .collect::<BTreeMap<_, _>>();
+        for (child, parent) in self.edges.iter() {
+            adjacency.entry(child).or_default().insert(parent);
+        }
+
+        adjacency
+    }
+
+    /// Extend the path within the component until it returns to its first table.
+    fn find_path_back(
+        &self,
+        start: &String,
+        component: &BTreeSet<String>,
+        path: &mut Vec<String>,
+    ) -> bool {
+        let last = path.last().unwrap().clone();
+        for parent in self.parents(&last) {
+            if parent == start {
+                path.push(start.clone());
+                return true;
+            }
+            if component.contains(parent) && !path.contains(parent) {
+                path.push(parent.clone());
+                if self.find_path_back(start, component, path) {
+                    return true;
+                }
+                path.pop();
+            }
+        }
+
+        false
+    }
+
+    /// Tables ordered so that parents come before children, and the edges ignored to break cycles
+    /// Ties are broken by name. When every remaining table waits for a parent, a table in a cycle
+    /// whose remaining parents are all in the same cycle is placed first by ignoring its edges to
+    /// them. The one with the fewest remaining parents is chosen.
+    pub(crate) fn topological_order(&self) -> (Vec<String>, Vec<(String, String)>) {
+        let adjacency = self.adjacency();
+        let components = self.strongly_connected_components();
+        let component_of = components
+            .iter()
+            .enumerate()
+            .flat_map(|(i, component)| component.iter().map(move |name| (name, i)))
+            .collect::<BTreeMap<&String, usize>>();
+        let mut children: BTreeMap<&String, BTreeSet<&String>> = BTreeMap::new();
+        let mut waiting = BTreeMap::new();
+        for (&child, parents) in adjacency.iter() {
+            let parents = parents.iter().filter(|parent| **parent != child);
+            waiting.insert(child, parents.clone().count());
+            for &parent in parents {
+                children.entry(parent).or_default().insert(child);
+            }
+        }
+        let mut ignored = self
+            .edges
+            .iter()
+            .filter(|(child, parent)| child == parent)
+            .cloned()
+            .collect::<BTreeSet<(String, String)>>();
+        let mut ready = waiting
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(name, _)| *name)
+            .collect::<BTreeSet<&String>>();
+
+        let mut order = Vec::new();
+        while !waiting.is_empty() {
+            let next = match ready.pop_first() {
+                Some(name) => name,
+                None => {
+                    // A cycle exists among the remaining tables, and one of them waits only for
+                    // the tables of its own cycle
+                    let (&name, _) = waiting
+                        .iter()
+                        .filter(|(name, _)| {
+                            let component = component_of.get(*name);
+                            component.is_some()
+                                && adjacency[*name].iter().all(|parent| {
+                                    !waiting.contains_key(parent)
+                                        || component_of.get(parent) == component
+                                })
+                        })
+                        .min_by_key(|(_, count)| **count)
+                        .unwrap();
+                    for parent in adjacency[name].iter() {
+                        if *parent != name && waiting.contains_key(parent) {
+                            ignored.insert((name.clone(), (*parent).clone()));
+                        }
+                    }
+                    name
+                }
+            };
+            waiting.remove(next);
+            for child in children.get(next).into_iter().flatten() {
+                if let Some(count) = waiting.get_mut(child) {
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.insert(child);
+                    }
+                }
+            }
+            order.push(next.clone());
+        }
+
+        (order, ignored.into_iter().collect())
+    }
+}
+
+/// Longest chain of parents starting from the table, memoized by table
+fn longest_chain_from(
+    adjacency: &BTreeMap<&String, BTreeSet<&String>>,
+    node: &String,
+    is_acyclic_edge: &impl Fn(&String, &String) -> bool,
+    memo: &mut BTreeMap<String, Vec<String>>,
+) -> Vec<String> {
+    if let Some(chain) = memo.get(node) {
+        return chain.clone();
+    }
+    let mut longest = Vec::new();
+    for parent in adjacency[node].iter() {
+        if *parent == node || !is_acyclic_edge(node, parent) {
+            continue;
+        }
+        let chain = longest_chain_from(adjacency, parent, is_acyclic_edge, memo);
+        if chain.len() > longest.len() {
+            longest = chain;
+        }
+    }
+    let chain = std::iter::once(node.clone())
+        .chain(longest)
+        .collect::<Vec<String>>();
+    memo.insert(node.clone(), chain.clone());
+
+    chain
+}
+
+/// State of Tarjan's algorithm for strongly connected components
+struct Tarjan<'a> {
+    adjacency: &'a BTreeMap<&'a String, BTreeSet<&'a String>>,
+    indexes: BTreeMap<&'a String, usize>,
+    lowlinks: BTreeMap<&'a String, usize>,
+    stack: Vec<&'a String>,
+    on_stack: BTreeSet<&'a String>,
+    components: Vec<BTreeSet<String>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, node: &'a String) {
+        let index = self.indexes.len();
+        self.indexes.insert(node, index);
+        self.lowlinks.insert(node, index);
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        let adjacency = self.adjacency;
+        for &parent in adjacency[node].iter() {
+            if !self.indexes.contains_key(parent) {
+                self.visit(parent);
+                let lowlink = self.lowlinks[node].min(self.lowlinks[parent]);
+                self.lowlinks.insert(node, lowlink);
+            } else if self.on_stack.contains(parent) {
+                let lowlink = self.lowlinks[node].min(self.indexes[parent]);
+                self.lowlinks.insert(node, lowlink);
+            }
+        }
+
+        if self.lowlinks[node] == index {
+            let mut component = BTreeSet::new();
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.remove(member);
+                component.insert(member.clone());
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Graph of the edges from children to parents, and the tables without relations
+    fn graph(edges: &[(&str, &str)], nodes: &[&str]) -> Graph {
+        Graph {
+            nodes: edges
+                .iter()
+                .flat_map(|(child, parent)| [*child, *parent])
+                .chain(nodes.iter().copied())
+                .map(|name| name.to_string())
+                .collect(),
+            edges: edges
+                .iter()
+                .map(|(child, parent)| (child.to_string(), parent.to_string()))
+                .collect(),
+        }
+    }
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn edges(edges: &[(&str, &str)]) -> Vec<(String, String)> {
+        edges
+            .iter()
+            .map(|(child, parent)| (child.to_string(), parent.to_string()))
+            .collect()
+    }
+
+    fn sets(sets: &[&[&str]]) -> Vec<BTreeSet<String>> {
+        sets.iter()
+            .map(|names| names.iter().map(|name| name.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn strongly_connected_components_with_self_references() {
+        let graph = graph(
+            &[("a", "a"), ("b", "a"), ("c", "d"), ("d", "c"), ("e", "c")],
+            &["f"],
+        );
+        assert_eq!(
+            graph.strongly_connected_components(),
+            sets(&[&["a"], &["c", "d"]])
+        );
+        assert_eq!(
+            graph.cycles(),
+            vec![strings(&["a", "a"]), strings(&["c", "d", "c"])]
+        );
+    }
+
+    #[test]
+    fn separate_cycles() {
+        let graph = graph(
+            &[
+                ("a", "b"),
+                ("b", "c"),
+                ("c", "a"),
+                ("c", "x"),
+                ("x", "y"),
+                ("y", "x"),
+            ],
+            &[],
+        );
+        assert_eq!(
+            graph.strongly_connected_components(),
+            sets(&[&["a", "b", "c"], &["x", "y"]])
+        );
+        assert_eq!(
+            graph.cycles(),
+            vec![strings(&["a", "b", "c", "a"]), strings(&["x", "y", "x"])]
+        );
+    }
+
+    #[test]
+    fn longest_chains_through_cycles() {
+        assert!(graph(&[("x", "y"), ("y", "x")], &[])
+            .longest_chains()
+            .is_empty());
+
+        // The edges between `a` and `b` are in a cycle, so chains end at `b`
+        let graph = graph(
+            &[
+                ("a", "b"),
+                ("b", "a"),
+                ("c", "b"),
+                ("d", "c"),
+                ("e", "d"),
+                ("e", "e"),
+                ("z", "c"),
+            ],
+            &["orphan"],
+        );
+        assert_eq!(
+            graph.longest_chains(),
+            vec![strings(&["e", "d", "c", "b"]), strings(&["z", "c", "b"])]
+        );
+    }
+
+    #[test]
+    fn topological_order_parents_first() {
+        let graph = graph(&[("comments", "posts"), ("posts", "users")], &[]);
+        assert_eq!(
+            graph.topological_order(),
+            (strings(&["users", "posts", "comments"]), Vec::new())
+        );
+    }
+
+    #[test]
+    fn topological_order_ties_by_name() {
+        let graph = graph(&[("b", "c"), ("a", "c"), ("d", "a")], &["e", "0"]);
+        assert_eq!(
+            graph.topological_order(),
+            (strings(&["0", "c", "a", "b", "d", "e"]), Vec::new())
+        );
+    }
+
+    #[test]
+    fn topological_order_ignores_self_references() {
+        let graph = graph(&[("a", "a"), ("b", "a")], &[]);
+        assert_eq!(
+            graph.topological_order(),
+            (strings(&["a", "b"]), edges(&[("a", "a")]))
+        );
+    }
+
+    #[test]
+    fn topological_order_breaks_cycles_within_the_cycle() {
+        // `a` is not in the cycle, so its edge to `x` is kept
+        let graph = graph(&[("x", "y"), ("y", "x"), ("a", "x")], &[]);
+        assert_eq!(
+            graph.topological_order(),
+            (strings(&["x", "a", "y"]), edges(&[("x", "y")]))
+        );
+    }
+
+    #[test]
+    fn topological_order_breaks_each_cycle() {
+        // `a` waits for the other cycle, so the cycle of `a` and `b` is broken at `b`
+        let graph = graph(
+            &[("a", "b"), ("b", "a"), ("a", "x"), ("x", "y"), ("y", "x")],
+            &[],
+        );
+        assert_eq!(
+            graph.topological_order(),
+            (
+                strings(&["b", "x", "a", "y"]),
+                edges(&[("b", "a"), ("x", "y")])
+            )
+        );
+    }
+
+    #[test]
+    fn topological_order_breaks_cycles_at_the_fewest_parents() {
+        let graph = graph(
+            &[("a", "b"), ("a", "c"), ("b", "a"), ("b", "c"), ("c", "a")],
+            &[],
+        );
+        assert_eq!(
+            graph.topological_order(),
+            (strings(&["c", "a", "b"]), edges(&[("a", "b"), ("c", "a")]))
+        );
+    }
+}
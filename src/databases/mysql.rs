@@ -1,4 +1,4 @@
-use crate::databases::{Column, Index, Table};
+use crate::databases::{Column, ForeignKey, Index, Table, TableFilter};
 use anyhow::Result;
 use sqlx::{Executor, MySql};
 use std::collections::HashSet;
@@ -10,6 +10,7 @@ pub(crate) use query::*;
 pub(crate) async fn tables<'a, E>(
     executor: E,
     database_names: HashSet<String>,
+    filter: &TableFilter,
 ) -> Result<Vec<Table>>
 where
     E: Executor<'a, Database = MySql> + Clone,
@@ -17,25 +18,36 @@ where
     let mut table_names = HashSet::new();
     for database_name in database_names.iter() {
         let tables = show_tables(executor.clone(), database_name).await?;
-        for table_name in tables.into_iter() {
+        for table_name in tables
+            .into_iter()
+            .filter(|table_name| filter.matches(database_name, table_name))
+        {
             table_names.insert((database_name.clone(), table_name));
         }
     }
 
     let mut tables = Vec::new();
     for (database_name, table_name) in table_names.into_iter() {
+        let column_details =
+            show_column_details(executor.clone(), &database_name, &table_name).await?;
         let mut columns = Vec::new();
         for (field, data_type, _, _, _, extra) in
             describe_table(executor.clone(), &database_name, &table_name).await?
         {
+            let details = column_details
+                .iter()
+                .find(|(column_name, _, _, _)| column_name == &field);
             columns.push(Column {
                 name: field,
                 data_type,
                 is_auto_increment: extra == Some("auto_increment".to_string()),
+                comment: details.and_then(|(_, comment, _, _)| non_empty(comment)),
+                character_set: details.and_then(|(_, _, character_set, _)| character_set.clone()),
+                collation: details.and_then(|(_, _, _, collation)| collation.clone()),
             });
         }
         let mut indexes: Vec<Index> = Vec::new();
-        for (_, non_unique, key_name, _, column_name, _, _, _, _, _, _, _, _) in
+        for (_, non_unique, key_name, _, column_name, _, _, _, _, _, _, _, index_comment) in
             show_indexes(executor.clone(), &database_name, &table_name).await?
         {
             let column = columns
@@ -43,30 +55,61 @@ where
                 .find(|column| column.name == column_name)
                 .unwrap()
                 .clone();
-            if let Some(i) = indexes.iter().position(|index| index.name == key_name) {
-                let index = indexes[i].clone();
-                indexes.insert(
-                    i,
-                    Index {
-                        columns: index.columns.into_iter().chain(vec![column]).collect(),
-                        ..index
-                    },
-                );
+            if let Some(index) = indexes.iter_mut().find(|index| index.name == key_name) {
+                index.columns.push(column);
             } else {
                 indexes.push(Index {
                     name: key_name.clone(),
                     columns: vec![column],
                     is_unique: !non_unique,
+                    comment: non_empty(&index_comment),
                 });
             }
         }
+        let mut foreign_keys: Vec<ForeignKey> = Vec::new();
+        for (
+            constraint_name,
+            column_name,
+            referenced_table_schema,
+            referenced_table_name,
+            referenced_column_name,
+        ) in show_foreign_keys(executor.clone(), &database_name, &table_name).await?
+        {
+            let column = columns
+                .iter()
+                .find(|column| column.name == column_name)
+                .unwrap()
+                .clone();
+            if let Some(foreign_key) = foreign_keys
+                .iter_mut()
+                .find(|foreign_key| foreign_key.name == constraint_name)
+            {
+                foreign_key.columns.push(column);
+                foreign_key.parent_columns.push(referenced_column_name);
+            } else {
+                foreign_keys.push(ForeignKey {
+                    name: constraint_name,
+                    columns: vec![column],
+                    parent_database: referenced_table_schema,
+                    parent_table: referenced_table_name,
+                    parent_columns: vec![referenced_column_name],
+                });
+            }
+        }
+        let comment = show_table_comment(executor.clone(), &database_name, &table_name).await?;
         tables.push(Table {
+            comment: non_empty(&comment),
             name: table_name,
             database: database_name,
             columns,
             indexes,
+            foreign_keys,
         });
     }
 
     Ok(tables)
 }
+
+fn non_empty(s: &str) -> Option<String> {
+    (!s.is_empty()).then(|| s.to_string())
+}
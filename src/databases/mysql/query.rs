@@ -95,3 +95,124 @@ where
         .await?;
     Ok(rows)
 }
+
+pub async fn show_foreign_keys<'a, E>(
+    executor: E,
+    database_name: &str,
+    table_name: &str,
+) -> Result<
+    Vec<(
+        String, // CONSTRAINT_NAME
+        String, // COLUMN_NAME
+        String, // REFERENCED_TABLE_SCHEMA
+        String, // REFERENCED_TABLE_NAME
+        String, // REFERENCED_COLUMN_NAME
+    )>,
+>
+where
+    E: Executor<'a, Database = MySql>,
+{
+    let query = r#"
+        SELECT CONSTRAINT_NAME, COLUMN_NAME, REFERENCED_TABLE_SCHEMA, REFERENCED_TABLE_NAME,
+            REFERENCED_COLUMN_NAME
+        FROM information_schema.KEY_COLUMN_USAGE
+        WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? AND REFERENCED_TABLE_NAME IS NOT NULL
+        ORDER BY CONSTRAINT_NAME, ORDINAL_POSITION
+    "#;
+    let rows = sqlx::query(query)
+        .bind(database_name)
+        .bind(table_name)
+        .try_map(|row: MySqlRow| {
+            Ok((
+                row.try_get("CONSTRAINT_NAME")?,
+                row.try_get("COLUMN_NAME")?,
+                row.try_get("REFERENCED_TABLE_SCHEMA")?,
+                row.try_get("REFERENCED_TABLE_NAME")?,
+                row.try_get("REFERENCED_COLUMN_NAME")?,
+            ))
+        })
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn show_table_comment<'a, E>(
+    executor: E,
+    database_name: &str,
+    table_name: &str,
+) -> Result<String>
+where
+    E: Executor<'a, Database = MySql>,
+{
+    let query = r#"
+        SELECT TABLE_COMMENT
+        FROM information_schema.TABLES
+        WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
+    "#;
+    let comment = sqlx::query(query)
+        .bind(database_name)
+        .bind(table_name)
+        .try_map(|row: MySqlRow| row.try_get("TABLE_COMMENT"))
+        .fetch_one(executor)
+        .await?;
+    Ok(comment)
+}
+
+pub async fn show_column_details<'a, E>(
+    executor: E,
+    database_name: &str,
+    table_name: &str,
+) -> Result<
+    Vec<(
+        String,         // COLUMN_NAME
+        String,         // COLUMN_COMMENT
+        Option<String>, // CHARACTER_SET_NAME
+        Option<String>, // COLLATION_NAME
+    )>,
+>
+where
+    E: Executor<'a, Database = MySql>,
+{
+    let query = r#"
+        SELECT COLUMN_NAME, COLUMN_COMMENT, CHARACTER_SET_NAME, COLLATION_NAME
+        FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?
+        ORDER BY ORDINAL_POSITION
+    "#;
+    let rows = sqlx::query(query)
+        .bind(database_name)
+        .bind(table_name)
+        .try_map(|row: MySqlRow| {
+            Ok((
+                row.try_get("COLUMN_NAME")?,
+                row.try_get("COLUMN_COMMENT")?,
+                row.try_get("CHARACTER_SET_NAME")?,
+                row.try_get("COLLATION_NAME")?,
+            ))
+        })
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn select_strings<'a, E>(executor: E, query: &str) -> Result<Vec<String>>
+where
+    E: Executor<'a, Database = MySql>,
+{
+    let rows = sqlx::query(query)
+        .try_map(|row: MySqlRow| row.try_get(0))
+        .fetch_all(executor)
+        .await?;
+    Ok(rows)
+}
+
+pub async fn select_count<'a, E>(executor: E, query: &str) -> Result<i64>
+where
+    E: Executor<'a, Database = MySql>,
+{
+    let count = sqlx::query(query)
+        .try_map(|row: MySqlRow| row.try_get(0))
+        .fetch_one(executor)
+        .await?;
+    Ok(count)
+}
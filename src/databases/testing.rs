@@ -0,0 +1,91 @@
+use crate::databases::{Column, ForeignKey, Index, Relation, Table};
+
+/// Column of the data type
+pub(crate) fn column(name: &str, data_type: &str) -> Column {
+    Column {
+        name: name.to_string(),
+        data_type: data_type.to_string(),
+        is_auto_increment: false,
+        comment: None,
+        character_set: None,
+        collation: None,
+    }
+}
+
+/// Table of `app` with `int` columns, whose primary key is `id` if it has the column
+pub(crate) fn table(name: &str, columns: &[&str]) -> Table {
+    let columns = columns
+        .iter()
+        .map(|c| column(c, "int"))
+        .collect::<Vec<Column>>();
+    let indexes = columns
+        .iter()
+        .filter(|c| c.name == "id")
+        .map(|c| Index {
+            name: "PRIMARY".to_string(),
+            columns: vec![c.clone()],
+            is_unique: true,
+            comment: None,
+        })
+        .collect();
+
+    Table {
+        name: name.to_string(),
+        database: "app".to_string(),
+        columns,
+        indexes,
+        foreign_keys: Vec::new(),
+        comment: None,
+    }
+}
+
+/// Table with a foreign key to the table of the same database
+pub(crate) fn with_foreign_key(
+    mut table: Table,
+    columns: &[&str],
+    parent_table: &str,
+    parent_columns: &[&str],
+) -> Table {
+    table.foreign_keys.push(ForeignKey {
+        name: format!("fk_{}_{}", table.name, columns.join("_")),
+        columns: columns.iter().map(|c| find_column(&table, c)).collect(),
+        parent_database: table.database.clone(),
+        parent_table: parent_table.to_string(),
+        parent_columns: parent_columns.iter().map(|c| c.to_string()).collect(),
+    });
+    table
+}
+
+/// Relation between the tables found by name
+pub(crate) fn relation(
+    tables: &[Table],
+    table: &str,
+    columns: &[&str],
+    parent_table: &str,
+    parent_columns: &[&str],
+    is_virtual: bool,
+) -> Relation {
+    let find_table = |name: &str| tables.iter().find(|t| t.name == name).unwrap().clone();
+    let table = find_table(table);
+    let parent_table = find_table(parent_table);
+
+    Relation {
+        columns: columns.iter().map(|c| find_column(&table, c)).collect(),
+        parent_columns: parent_columns
+            .iter()
+            .map(|c| find_column(&parent_table, c))
+            .collect(),
+        table,
+        parent_table,
+        is_virtual,
+    }
+}
+
+fn find_column(table: &Table, name: &str) -> Column {
+    table
+        .columns
+        .iter()
+        .find(|c| c.name == name)
+        .unwrap()
+        .clone()
+}
@@ -0,0 +1,17 @@
+mod dbml;
+mod diesel;
+mod dot;
+mod mermaid;
+mod migration;
+mod puml;
+mod sea_orm;
+mod subset;
+
+pub(crate) use dbml::*;
+pub(crate) use diesel::*;
+pub(crate) use dot::*;
+pub(crate) use mermaid::*;
+pub(crate) use migration::*;
+pub(crate) use puml::*;
+pub(crate) use sea_orm::*;
+pub(crate) use subset::*;
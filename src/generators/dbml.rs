@@ -0,0 +1,145 @@
+use crate::databases::{Relation, Table};
+use itertools::Itertools;
+
+/// Render the tables and the relations as DBML for dbdiagram.io
+/// ```text
+/// Table users {
+///   id "bigint unsigned" [pk, increment]
+///   email varchar(255) [unique]
+/// }
+///
+/// Ref: posts.user_id > users.id
+/// ```
+pub(crate) fn dbml(tables: &[Table], relations: &[Relation]) -> String {
+    let mut lines = Vec::new();
+    for table in tables.iter().sorted_by(|a, b| a.name.cmp(&b.name)) {
+        let primary_key = table.primary_key();
+        lines.push(format!("Table {} {{", identifier(&table.name)));
+        for column in table.columns.iter() {
+            let mut settings = Vec::new();
+            if let Some(pk) = primary_key {
+                if pk.columns.len() == 1 && pk.columns[0].name == column.name {
+                    settings.push("pk".to_string());
+                }
+            }
+            if table.indexes.iter().any(|index| {
+                index.is_unique
+                    && index.name != "PRIMARY"
+                    && index.columns.len() == 1
+                    && index.columns[0].name == column.name
+            }) {
+                settings.push("unique".to_string());
+            }
+            if column.is_auto_increment {
+                settings.push("increment".to_string());
+            }
+            if let Some(comment) = &column.comment {
+                settings.push(format!("note: {}", string(comment)));
+            }
+            lines.push(
+                format!(
+                    "  {} {} {}",
+                    identifier(&column.name),
+                    column_type(&column.data_type),
+                    if settings.is_empty() {
+                        String::new()
+                    } else {
+                        format!("[{}]", settings.join(", "))
+                    }
+                )
+                .trim_end()
+                .to_string(),
+            );
+        }
+
+        let indexes = table
+            .indexes
+            .iter()
+            .filter(|index| index.name != "PRIMARY" || index.columns.len() > 1)
+            .collect::<Vec<_>>();
+        if !indexes.is_empty() {
+            lines.push(String::new());
+            lines.push("  indexes {".to_string());
+            for index in indexes {
+                let settings = if index.name == "PRIMARY" {
+                    "pk".to_string()
+                } else if index.is_unique {
+                    format!("unique, name: {}", string(&index.name))
+                } else {
+                    format!("name: {}", string(&index.name))
+                };
+                lines.push(format!(
+                    "    {} [{}]",
+                    columns(index.columns.iter().map(|c| &c.name)),
+                    settings
+                ));
+            }
+            lines.push("  }".to_string());
+        }
+        if let Some(comment) = &table.comment {
+            lines.push(String::new());
+            lines.push(format!("  Note: {}", string(comment)));
+        }
+        lines.push("}".to_string());
+        lines.push(String::new());
+    }
+    for r in relations.iter().sorted_by(|a, b| {
+        (&a.table.name, &a.parent_table.name).cmp(&(&b.table.name, &b.parent_table.name))
+    }) {
+        lines.push(format!(
+            "Ref: {}.{} {} {}.{}{}",
+            identifier(&r.table.name),
+            columns(r.columns.iter().map(|c| &c.name)),
+            if r.table.is_unique_columns(&r.columns) {
+                "-"
+            } else {
+                ">"
+            },
+            identifier(&r.parent_table.name),
+            columns(r.parent_columns.iter().map(|c| &c.name)),
+            if r.is_virtual { " // virtual" } else { "" }
+        ));
+    }
+
+    lines.join("\n").trim_end().to_string() + "\n"
+}
+
+/// A single column as is, and composite columns as `(a, b)`
+fn columns<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    let names = names.map(|name| identifier(name)).collect::<Vec<String>>();
+    if names.len() == 1 {
+        names[0].clone()
+    } else {
+        format!("({})", names.join(", "))
+    }
+}
+
+fn identifier(name: &str) -> String {
+    if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name)
+    }
+}
+
+/// Single-quoted string with quotes, backslashes and line breaks escaped
+fn string(s: &str) -> String {
+    format!(
+        "'{}'",
+        s.replace('\\', "\\\\")
+            .replace('\'', "\\'")
+            .replace('\n', "\\n")
+    )
+}
+
+/// Types with spaces or quotes such as `bigint unsigned` or `enum('a','b')` need to be quoted
+fn column_type(data_type: &str) -> String {
+    if data_type
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_(),".contains(c))
+    {
+        data_type.to_string()
+    } else {
+        format!("\"{}\"", data_type.replace('"', "\\\""))
+    }
+}
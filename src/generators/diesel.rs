@@ -0,0 +1,92 @@
+use crate::databases::{Relation, Table};
+use itertools::Itertools;
+
+/// Render the relations as Diesel `joinable!` and `allow_tables_to_appear_in_same_query!` declarations
+/// Diesel supports only a single relation with a single column between two tables, and joins on the
+/// primary key of the parent table, so the others are output as comments.
+pub(crate) fn diesel(tables: &[Table], relations: &[Relation]) -> String {
+    let mut lines = Vec::new();
+    let mut joined: Vec<(&String, &String)> = Vec::new();
+    for r in relations.iter().sorted_by_key(|r| {
+        (
+            &r.table.name,
+            &r.parent_table.name,
+            r.columns.iter().map(|c| &c.name).collect::<Vec<_>>(),
+        )
+    }) {
+        let columns = r.columns.iter().map(|c| &c.name).join(", ");
+        let pair = (&r.table.name, &r.parent_table.name);
+        let is_primary_key = r.parent_table.primary_key().is_some_and(|pk| {
+            pk.columns
+                .iter()
+                .map(|c| &c.name)
+                .eq(r.parent_columns.iter().map(|c| &c.name))
+        });
+        if r.columns.len() != 1
+            || !is_primary_key
+            || r.table.name == r.parent_table.name
+            || joined.contains(&pair)
+        {
+            lines.push(format!(
+                "// diesel::joinable!({} -> {} ({}));",
+                r.table.name, r.parent_table.name, columns
+            ));
+            continue;
+        }
+        lines.push(format!(
+            "diesel::joinable!({} -> {} ({}));",
+            r.table.name, r.parent_table.name, columns
+        ));
+        joined.push(pair);
+    }
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    lines.push("diesel::allow_tables_to_appear_in_same_query!(".to_string());
+    for name in tables.iter().map(|t| &t.name).sorted().dedup() {
+        lines.push(format!("    {},", name));
+    }
+    lines.push(");".to_string());
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::{relation, table};
+
+    #[test]
+    fn joinable_only_on_primary_keys() {
+        let tables = vec![
+            table("users", &["id", "email"]),
+            table("posts", &["id", "users_id", "users_email"]),
+        ];
+        let relations = vec![
+            relation(
+                &tables,
+                "posts",
+                &["users_email"],
+                "users",
+                &["email"],
+                true,
+            ),
+            relation(&tables, "posts", &["users_id"], "users", &["id"], true),
+        ];
+        assert_eq!(
+            diesel(&tables, &relations),
+            "// diesel::joinable!(posts -> users (users_email));\n\
+             diesel::joinable!(posts -> users (users_id));\n\
+             \n\
+             diesel::allow_tables_to_appear_in_same_query!(\n    posts,\n    users,\n);\n"
+        );
+    }
+
+    #[test]
+    fn relations_without_columns() {
+        let tables = vec![table("users", &["id"]), table("posts", &["id"])];
+        let relations = vec![relation(&tables, "posts", &[], "users", &[], true)];
+        assert!(diesel(&tables, &relations).starts_with("// diesel::joinable!(posts -> users ());"));
+    }
+}
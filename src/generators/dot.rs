@@ -0,0 +1,124 @@
+use crate::databases::{Relation, Table};
+use itertools::Itertools;
+
+/// Render the tables and the relations as a Graphviz DOT digraph
+/// Relations declared as foreign keys are drawn with solid edges, and virtual relations with dashed edges.
+pub(crate) fn dot(tables: &[Table], relations: &[Relation]) -> String {
+    let mut lines = vec![
+        "digraph schema {".to_string(),
+        "  graph [rankdir=LR, fontname=\"Helvetica\"];".to_string(),
+        "  node [shape=plaintext, fontname=\"Helvetica\"];".to_string(),
+        "  edge [fontname=\"Helvetica\", fontsize=10, arrowhead=normal, arrowtail=crow, dir=both];"
+            .to_string(),
+    ];
+    for table in tables.iter().sorted_by(|a, b| a.name.cmp(&b.name)) {
+        let primary_key = table.primary_key();
+        let rows = table
+            .columns
+            .iter()
+            .map(|column| {
+                let name = if primary_key
+                    .map(|pk| pk.columns.iter().any(|c| c.name == column.name))
+                    .unwrap_or(false)
+                {
+                    format!("<u>{}</u>", escape(&column.name))
+                } else {
+                    escape(&column.name)
+                };
+                format!(
+                    "<tr><td port=\"{}\" align=\"left\">{} <font color=\"#666666\">{}</font></td></tr>",
+                    escape(&column.name),
+                    name,
+                    escape(&column.data_type)
+                )
+            })
+            .join("");
+        lines.push(format!(
+            "  {} [label=<<table border=\"0\" cellborder=\"1\" cellspacing=\"0\"><tr><td bgcolor=\"#efefef\"><b>{}</b></td></tr>{}</table>>];",
+            quote(&table.name),
+            escape(&table.name),
+            rows
+        ));
+    }
+    for r in relations.iter().sorted_by(|a, b| {
+        (&a.table.name, &a.parent_table.name).cmp(&(&b.table.name, &b.parent_table.name))
+    }) {
+        let (child, parent) = match (r.columns.as_slice(), r.parent_columns.as_slice()) {
+            ([column], [parent_column]) => (
+                format!("{}:{}", quote(&r.table.name), quote(&column.name)),
+                format!(
+                    "{}:{}",
+                    quote(&r.parent_table.name),
+                    quote(&parent_column.name)
+                ),
+            ),
+            _ => (quote(&r.table.name), quote(&r.parent_table.name)),
+        };
+        lines.push(format!(
+            "  {} -> {} [style={}, tooltip={}];",
+            child,
+            parent,
+            if r.is_virtual { "dashed" } else { "solid" },
+            quote(&r.definition())
+        ));
+    }
+    lines.push("}".to_string());
+
+    lines.join("\n") + "\n"
+}
+
+/// Quoted DOT identifier of node ids, ports and attributes
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "\\\""))
+}
+
+/// Escaped text of the HTML-like labels, whose port attributes resolve to the raw names
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::{column, relation, table};
+
+    #[test]
+    fn special_characters() {
+        let mut child = table("order items", &["id"]);
+        child.columns.push(column("parent\"id", "int"));
+        child.columns.push(column("a:b", "int"));
+        let mut parent = table("my-orders", &["id"]);
+        parent.columns.push(column("x-y", "int"));
+        let tables = vec![child, parent];
+        let relations = vec![
+            relation(
+                &tables,
+                "order items",
+                &["parent\"id"],
+                "my-orders",
+                &["id"],
+                true,
+            ),
+            relation(
+                &tables,
+                "order items",
+                &["a:b"],
+                "my-orders",
+                &["x-y"],
+                true,
+            ),
+        ];
+        let output = dot(&tables, &relations);
+
+        assert!(output.contains("  \"order items\" [label=<"));
+        assert!(output.contains("<td port=\"parent&quot;id\" align=\"left\">"));
+        assert!(output.contains("  \"my-orders\" [label=<"));
+        assert!(output.contains(
+            "  \"order items\":\"parent\\\"id\" -> \"my-orders\":\"id\" [style=dashed, tooltip=\"order items(parent\\\"id) -> my-orders(id)\"];"
+        ));
+        assert!(output.contains("  \"order items\":\"a:b\" -> \"my-orders\":\"x-y\" [style=dashed"));
+    }
+}
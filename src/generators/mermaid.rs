@@ -0,0 +1,79 @@
+use crate::databases::{Relation, Table};
+use itertools::Itertools;
+
+/// Render the tables and the relations as a Mermaid `erDiagram`
+/// ```text
+/// erDiagram
+///     users {
+///         bigint id PK
+///     }
+///     posts {
+///         bigint id PK
+///         bigint user_id FK
+///     }
+///     users ||--o{ posts : "user_id"
+/// ```
+pub(crate) fn mermaid(tables: &[Table], relations: &[Relation]) -> String {
+    let mut lines = vec!["erDiagram".to_string()];
+    for table in tables.iter().sorted_by(|a, b| a.name.cmp(&b.name)) {
+        lines.push(format!("    {} {{", table.name));
+        for column in table.columns.iter() {
+            let mut keys = Vec::new();
+            if table
+                .primary_key()
+                .map(|pk| pk.columns.iter().any(|c| c.name == column.name))
+                .unwrap_or(false)
+            {
+                keys.push("PK");
+            }
+            if relations.iter().any(|r| {
+                r.table.name == table.name && r.columns.iter().any(|c| c.name == column.name)
+            }) {
+                keys.push("FK");
+            }
+            lines.push(
+                format!(
+                    "        {} {} {}",
+                    attribute_type(&column.data_type),
+                    column.name,
+                    keys.join(",")
+                )
+                .trim_end()
+                .to_string(),
+            );
+        }
+        lines.push("    }".to_string());
+    }
+    for r in relations.iter().sorted_by(|a, b| {
+        (&a.parent_table.name, &a.table.name).cmp(&(&b.parent_table.name, &b.table.name))
+    }) {
+        let cardinality = if r.table.is_unique_columns(&r.columns) {
+            "||--o|"
+        } else {
+            "||--o{"
+        };
+        lines.push(format!(
+            "    {} {} {} : \"{}\"",
+            r.parent_table.name,
+            cardinality,
+            r.table.name,
+            r.columns.iter().map(|c| &c.name).join(", ")
+        ));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// Mermaid does not allow spaces and most symbols in attribute types,
+/// so `bigint unsigned` becomes `bigint_unsigned` and `enum('a','b')` becomes `enum`.
+fn attribute_type(data_type: &str) -> String {
+    let data_type = data_type.split_whitespace().join("_");
+    if data_type
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-()".contains(c))
+    {
+        data_type
+    } else {
+        data_type.split('(').next().unwrap_or_default().to_string()
+    }
+}
@@ -0,0 +1,194 @@
+use crate::databases::{Relation, Table};
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use std::collections::HashSet;
+
+/// Maximum length of identifiers in MySQL
+const MAX_IDENTIFIER_LENGTH: usize = 64;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) enum Dialect {
+    MySql,
+}
+
+impl Dialect {
+    /// Dialect of the backend of the database URL scheme
+    pub(crate) fn from_scheme(scheme: &str) -> Result<Dialect> {
+        match scheme {
+            "mysql" => Ok(Dialect::MySql),
+            _ => bail!("unsupported database: `{}`", scheme),
+        }
+    }
+
+    pub(crate) fn quote(&self, identifier: &str) -> String {
+        match self {
+            Dialect::MySql => format!("`{}`", identifier.replace('`', "``")),
+        }
+    }
+}
+
+/// SQL statements to apply and to revert a migration
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Migration {
+    pub(crate) up: Vec<String>,
+    pub(crate) down: Vec<String>,
+}
+
+/// Generate statements that add the relations as foreign keys
+/// An index is created first when the child columns are not covered by any index.
+pub(crate) fn migration(relations: &[Relation], dialect: Dialect) -> Migration {
+    let mut migration = Migration::default();
+    let mut created_indexes: Vec<(String, Vec<String>)> = Vec::new();
+    // Foreign key names are unique in a database, and index names in a table
+    let mut constraint_names = relations
+        .iter()
+        .flat_map(|r| r.table.foreign_keys.iter().map(|f| f.name.clone()))
+        .collect::<HashSet<String>>();
+    let mut index_names = relations
+        .iter()
+        .flat_map(|r| {
+            r.table
+                .indexes
+                .iter()
+                .map(|i| format!("{}.{}", r.table.name, i.name))
+        })
+        .collect::<HashSet<String>>();
+    for r in relations.iter().sorted_by(|a, b| {
+        (&a.table.name, &a.parent_table.name).cmp(&(&b.table.name, &b.parent_table.name))
+    }) {
+        let child_table = table_name(&r.table, None, dialect);
+        let column_names = r.columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>();
+        let columns = r.columns.iter().map(|c| dialect.quote(&c.name)).join(", ");
+
+        let is_index_created = created_indexes
+            .iter()
+            .any(|(table, names)| table == &r.table.name && names.starts_with(&column_names));
+        if !r.table.has_index_on(&r.columns) && !is_index_created {
+            let index_name = unique_name(
+                identifier(&["idx", &r.table.name, &column_names.join("_")]),
+                |name| index_names.insert(format!("{}.{}", r.table.name, name)),
+            );
+            migration.up.push(format!(
+                "CREATE INDEX {} ON {} ({});",
+                dialect.quote(&index_name),
+                child_table,
+                columns
+            ));
+            migration.down.push(format!(
+                "DROP INDEX {} ON {};",
+                dialect.quote(&index_name),
+                child_table
+            ));
+            created_indexes.push((r.table.name.clone(), column_names.clone()));
+        }
+
+        let constraint_name = unique_name(
+            identifier(&[
+                "fk",
+                &r.table.name,
+                &column_names.join("_"),
+                &r.parent_table.name,
+            ]),
+            |name| constraint_names.insert(name.to_string()),
+        );
+        migration.up.push(format!(
+            "ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({});",
+            child_table,
+            dialect.quote(&constraint_name),
+            columns,
+            table_name(&r.parent_table, Some(&r.table), dialect),
+            r.parent_columns
+                .iter()
+                .map(|c| dialect.quote(&c.name))
+                .join(", ")
+        ));
+        migration.down.push(format!(
+            "ALTER TABLE {} DROP FOREIGN KEY {};",
+            child_table,
+            dialect.quote(&constraint_name)
+        ));
+    }
+    // Foreign keys are dropped before the indexes they depend on
+    migration.down.reverse();
+
+    migration
+}
+
+/// Generate statements that create indexes on the child columns not covered by any index
+pub(crate) fn missing_indexes(relations: &[Relation], dialect: Dialect) -> Vec<String> {
+    relations
+        .iter()
+        .filter(|r| !r.table.has_index_on(&r.columns))
+        .map(|r| {
+            (
+                &r.table,
+                r.columns.iter().map(|c| c.name.clone()).collect::<Vec<_>>(),
+            )
+        })
+        .sorted_by(|(a, a_columns), (b, b_columns)| (&a.name, a_columns).cmp(&(&b.name, b_columns)))
+        .dedup_by(|(a, a_columns), (b, b_columns)| a.name == b.name && a_columns == b_columns)
+        .map(|(table, column_names)| {
+            format!(
+                "CREATE INDEX {} ON {} ({});",
+                dialect.quote(&identifier(&["idx", &table.name, &column_names.join("_")])),
+                table_name(table, None, dialect),
+                column_names.iter().map(|c| dialect.quote(c)).join(", ")
+            )
+        })
+        .collect()
+}
+
+/// Table name qualified with the database only when it differs from the database of the origin
+fn table_name(table: &Table, origin: Option<&Table>, dialect: Dialect) -> String {
+    match origin {
+        Some(origin) if origin.database != table.database => format!(
+            "{}.{}",
+            dialect.quote(&table.database),
+            dialect.quote(&table.name)
+        ),
+        _ => dialect.quote(&table.name),
+    }
+}
+
+/// Identifier such as `fk_posts_user_id_users`
+/// Identifiers longer than the maximum length are truncated with a hash of the whole identifier
+/// appended, so that identifiers sharing a long prefix do not collide.
+fn identifier(parts: &[&str]) -> String {
+    let identifier = parts.join("_");
+    if identifier.chars().count() <= MAX_IDENTIFIER_LENGTH {
+        return identifier;
+    }
+    let hash = format!("{:08x}", fnv1a(&identifier));
+    identifier
+        .chars()
+        .take(MAX_IDENTIFIER_LENGTH - hash.len() - 1)
+        .chain(std::iter::once('_'))
+        .chain(hash.chars())
+        .collect()
+}
+
+/// The identifier, or the identifier with a numeric suffix if it is already used
+/// `insert` records the name and returns whether it was unused.
+fn unique_name(identifier: String, mut insert: impl FnMut(&str) -> bool) -> String {
+    if insert(&identifier) {
+        return identifier;
+    }
+    (2..)
+        .map(|n| {
+            let suffix = format!("_{}", n);
+            identifier
+                .chars()
+                .take(MAX_IDENTIFIER_LENGTH - suffix.len())
+                .chain(suffix.chars())
+                .collect::<String>()
+        })
+        .find(|name| insert(name))
+        .unwrap()
+}
+
+/// 32-bit FNV-1a hash, which is stable across builds unlike the hasher of the standard library
+fn fnv1a(s: &str) -> u32 {
+    s.bytes().fold(0x811c9dc5, |hash, b| {
+        (hash ^ b as u32).wrapping_mul(0x01000193)
+    })
+}
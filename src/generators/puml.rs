@@ -0,0 +1,62 @@
+use crate::databases::{Relation, Table};
+use itertools::Itertools;
+
+/// Render the tables and the relations as a PlantUML entity relationship diagram
+/// Relations declared as foreign keys are drawn with solid lines, and virtual relations with dotted lines.
+pub(crate) fn puml(tables: &[Table], relations: &[Relation]) -> String {
+    let mut lines = vec![
+        "@startuml".to_string(),
+        "hide circle".to_string(),
+        "skinparam linetype ortho".to_string(),
+        String::new(),
+    ];
+    for table in tables.iter().sorted_by(|a, b| a.name.cmp(&b.name)) {
+        let primary_key = table
+            .primary_key()
+            .map(|pk| pk.columns.clone())
+            .unwrap_or_default();
+        lines.push(format!("entity \"{}\" {{", table.name));
+        for column in primary_key.iter() {
+            lines.push(format!("  * {} : {} <<PK>>", column.name, column.data_type));
+        }
+        lines.push("  --".to_string());
+        for column in table
+            .columns
+            .iter()
+            .filter(|column| !primary_key.iter().any(|c| c.name == column.name))
+        {
+            let is_foreign_key = relations.iter().any(|r| {
+                r.table.name == table.name && r.columns.iter().any(|c| c.name == column.name)
+            });
+            lines.push(format!(
+                "  {} : {}{}",
+                column.name,
+                column.data_type,
+                if is_foreign_key { " <<FK>>" } else { "" }
+            ));
+        }
+        lines.push("}".to_string());
+        lines.push(String::new());
+    }
+    for r in relations.iter().sorted_by(|a, b| {
+        (&a.parent_table.name, &a.table.name).cmp(&(&b.parent_table.name, &b.table.name))
+    }) {
+        let line = if r.is_virtual { ".." } else { "--" };
+        let child = if r.table.is_unique_columns(&r.columns) {
+            "o|"
+        } else {
+            "o{"
+        };
+        lines.push(format!(
+            "\"{}\" ||{}{} \"{}\" : {}",
+            r.parent_table.name,
+            line,
+            child,
+            r.table.name,
+            r.columns.iter().map(|c| &c.name).join(", ")
+        ));
+    }
+    lines.push("@enduml".to_string());
+
+    lines.join("\n") + "\n"
+}
@@ -0,0 +1,161 @@
+use crate::databases::{Column, Relation, Table};
+use itertools::Itertools;
+
+/// Render the relations as SeaORM `Relation` enums and `Related` implementations for each entity
+pub(crate) fn sea_orm(tables: &[Table], relations: &[Relation]) -> String {
+    let mut blocks = Vec::new();
+    for table in tables.iter().sorted_by(|a, b| a.name.cmp(&b.name)) {
+        // (variant, attribute, related entity)
+        let mut variants: Vec<(String, String, Option<String>)> = Vec::new();
+
+        let belongs_to = relations
+            .iter()
+            .filter(|r| r.table.name == table.name)
+            .sorted_by_key(|r| {
+                (
+                    &r.parent_table.name,
+                    r.columns.iter().map(|c| &c.name).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<&Relation>>();
+        for r in belongs_to.iter() {
+            let is_self = r.parent_table.name == table.name;
+            let (entity, module) = if is_self {
+                ("Entity".to_string(), String::new())
+            } else {
+                (
+                    format!("super::{}::Entity", r.parent_table.name),
+                    format!("super::{}::", r.parent_table.name),
+                )
+            };
+            let is_ambiguous = belongs_to
+                .iter()
+                .filter(|other| other.parent_table.name == r.parent_table.name)
+                .count()
+                > 1;
+            let variant = match (is_self, is_ambiguous) {
+                (true, false) => "SelfRef".to_string(),
+                (_, false) => pascal_case(&r.parent_table.name),
+                (_, true) => format!(
+                    "{}{}",
+                    if is_self {
+                        "SelfRef".to_string()
+                    } else {
+                        pascal_case(&r.parent_table.name)
+                    },
+                    r.columns.iter().map(|c| pascal_case(&c.name)).join("")
+                ),
+            };
+            variants.push((
+                variant,
+                format!(
+                    "belongs_to = \"{}\",\n        from = \"{}\",\n        to = \"{}\"",
+                    entity,
+                    columns("", &r.columns),
+                    columns(&module, &r.parent_columns)
+                ),
+                (!is_self && !is_ambiguous).then(|| entity.clone()),
+            ));
+        }
+
+        let has = relations
+            .iter()
+            .filter(|r| r.parent_table.name == table.name && r.table.name != table.name)
+            .sorted_by(|a, b| a.table.name.cmp(&b.table.name))
+            .collect::<Vec<&Relation>>();
+        for r in has.iter() {
+            let is_ambiguous = has
+                .iter()
+                .filter(|other| other.table.name == r.table.name)
+                .count()
+                > 1;
+            let variant = pascal_case(&r.table.name);
+            // Relations from the same child table cannot be told apart without `belongs_to`
+            if is_ambiguous || variants.iter().any(|(v, _, _)| v == &variant) {
+                continue;
+            }
+            let entity = format!("super::{}::Entity", r.table.name);
+            let kind = if r.table.is_unique_columns(&r.columns) {
+                "has_one"
+            } else {
+                "has_many"
+            };
+            variants.push((variant, format!("{} = \"{}\"", kind, entity), Some(entity)));
+        }
+
+        let mut lines = vec![
+            format!("// {}.rs", table.name),
+            "#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]".to_string(),
+        ];
+        if variants.is_empty() {
+            lines.push("pub enum Relation {}".to_string());
+            blocks.push(lines.join("\n"));
+            continue;
+        }
+        lines.push("pub enum Relation {".to_string());
+        for (variant, attribute, _) in variants.iter() {
+            if attribute.contains('\n') {
+                lines.push(format!("    #[sea_orm(\n        {}\n    )]", attribute));
+            } else {
+                lines.push(format!("    #[sea_orm({})]", attribute));
+            }
+            lines.push(format!("    {},", variant));
+        }
+        lines.push("}".to_string());
+        for (variant, _, entity) in variants.iter() {
+            if let Some(entity) = entity {
+                lines.push(String::new());
+                lines.push(format!("impl Related<{}> for Entity {{", entity));
+                lines.push("    fn to() -> RelationDef {".to_string());
+                lines.push(format!("        Relation::{}.def()", variant));
+                lines.push("    }".to_string());
+                lines.push("}".to_string());
+            }
+        }
+        blocks.push(lines.join("\n"));
+    }
+
+    blocks.join("\n\n") + "\n"
+}
+
+/// `Column::UserId` for a single column, and `(Column::A, Column::B)` for composite columns
+fn columns(module: &str, columns: &[Column]) -> String {
+    let columns = columns
+        .iter()
+        .map(|c| format!("{}Column::{}", module, pascal_case(&c.name)))
+        .collect::<Vec<String>>();
+    if columns.len() == 1 {
+        columns[0].clone()
+    } else {
+        format!("({})", columns.join(", "))
+    }
+}
+
+fn pascal_case(s: &str) -> String {
+    s.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::{relation, table};
+
+    #[test]
+    fn relations_without_columns() {
+        let tables = vec![table("users", &["id"]), table("posts", &["id"])];
+        let relations = vec![
+            relation(&tables, "posts", &[], "users", &[], true),
+            relation(&tables, "posts", &[], "users", &[], true),
+        ];
+        assert!(sea_orm(&tables, &relations).contains("pub enum Relation {"));
+    }
+}
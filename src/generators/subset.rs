@@ -0,0 +1,394 @@
+use crate::databases::{Column, DataTypeFamily, Graph, Relation, Table};
+use crate::generators::Dialect;
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Rows of a table in the subset
+#[derive(Debug, Clone)]
+pub(crate) struct SubsetTable {
+    pub(crate) table: Table,
+    /// Number of relation hops from the root table
+    pub(crate) depth: usize,
+    /// Common table expressions selecting the rows, each of which refers only to the preceding ones
+    /// The last one selects the rows of this table.
+    pub(crate) ctes: Vec<(String, String)>,
+}
+
+impl SubsetTable {
+    /// Statement selecting the rows
+    pub(crate) fn select(&self, dialect: Dialect) -> String {
+        format!(
+            "{}SELECT * FROM {};",
+            self.with(dialect),
+            self.name(dialect)
+        )
+    }
+
+    /// Statement selecting each row as a quoted tuple of the values such as `(1, 'a', NULL)`
+    pub(crate) fn select_values(&self, dialect: Dialect) -> String {
+        format!(
+            "{}SELECT CONVERT(CONCAT('(', CONCAT_WS(', ', {}), ')') USING utf8mb4) FROM {}",
+            self.with(dialect),
+            self.table
+                .columns
+                .iter()
+                .map(|c| literal(&dialect.quote(&c.name), c))
+                .join(", "),
+            self.name(dialect)
+        )
+    }
+
+    /// Statement inserting the quoted tuples returned by `select_values`
+    pub(crate) fn insert(&self, values: &[String], dialect: Dialect) -> String {
+        format!(
+            "INSERT INTO {} ({}) VALUES\n{};",
+            qualified_name(&self.table, dialect),
+            self.table
+                .columns
+                .iter()
+                .map(|c| dialect.quote(&c.name))
+                .join(", "),
+            values.iter().map(|v| format!("  {}", v)).join(",\n")
+        )
+    }
+
+    fn with(&self, dialect: Dialect) -> String {
+        format!(
+            "WITH\n{}\n",
+            self.ctes
+                .iter()
+                .map(|(name, query)| format!("  {} AS ({})", dialect.quote(name), query))
+                .join(",\n")
+        )
+    }
+
+    fn name(&self, dialect: Dialect) -> String {
+        dialect.quote(&self.ctes.last().unwrap().0)
+    }
+}
+
+/// Rows of a table matching the condition, which may refer to the rows of other expressions
+#[derive(Debug, Clone)]
+struct Cte {
+    name: String,
+    table: Table,
+    condition: String,
+    /// Expressions referred to directly or indirectly
+    dependencies: BTreeSet<usize>,
+}
+
+/// Predicate of a condition, and the expression whose rows it refers to
+type Predicate = (String, Option<usize>);
+
+/// Tables reachable from the root table following the relations to children within the depth, and
+/// to parents at any distance so that every referenced row is included
+/// Rows of a table are those related to the rows of the tables one hop closer to the root. Then the
+/// rows of the parents referenced by the rows of the children are added to keep the subset
+/// consistent, except for the relations ignored to break cycles. Tables are ordered so that parents
+/// come before children.
+/// The rows of each table are selected once in a common table expression that the related tables
+/// refer to by name, so the statements grow linearly with the number of tables. Common table
+/// expressions require MySQL 8.0 or later.
+pub(crate) fn subset(
+    tables: &[Table],
+    relations: &[Relation],
+    root: &str,
+    condition: &str,
+    depth: usize,
+    dialect: Dialect,
+) -> Result<Vec<SubsetTable>> {
+    let root = tables
+        .iter()
+        .find(|t| t.name == root)
+        .ok_or_else(|| anyhow!("table not found: `{}`", root))?;
+
+    let mut ctes: Vec<Cte> = Vec::new();
+    // Expression of the rows of each table, which is replaced when the rows are extended
+    let mut current: BTreeMap<String, usize> = BTreeMap::new();
+    let mut depths: BTreeMap<String, usize> = BTreeMap::new();
+    // Predicates of the conditions, which are combined with OR
+    let mut predicates_of: BTreeMap<String, Vec<Predicate>> = BTreeMap::new();
+    let root_predicates = vec![(condition.to_string(), None)];
+    current.insert(
+        root.name.clone(),
+        push_cte(&mut ctes, root, &root_predicates),
+    );
+    depths.insert(root.name.clone(), 0);
+    predicates_of.insert(root.name.clone(), root_predicates);
+    // Relations followed from parents to children, whose parent rows are already in the subset
+    let mut followed_to_children = Vec::new();
+    for d in 1.. {
+        let mut predicates: BTreeMap<String, (Table, Vec<Predicate>)> = BTreeMap::new();
+        for (name, _) in depths.iter().filter(|(_, depth)| **depth == d - 1) {
+            let nested = current[name];
+            for (i, r) in relations.iter().enumerate() {
+                // Children of the rows within the depth, or parents referenced by the rows
+                let (table, columns, nested_columns) = if r.parent_table.name == *name && d <= depth
+                {
+                    (&r.table, &r.columns, &r.parent_columns)
+                } else if r.table.name == *name {
+                    (&r.parent_table, &r.parent_columns, &r.columns)
+                } else {
+                    continue;
+                };
+                if depths.contains_key(&table.name) {
+                    continue;
+                }
+                if r.parent_table.name == *name && d <= depth {
+                    followed_to_children.push(i);
+                }
+                predicates
+                    .entry(table.name.clone())
+                    .or_insert_with(|| (table.clone(), Vec::new()))
+                    .1
+                    .push(predicate(columns, nested_columns, &ctes, nested, dialect));
+            }
+        }
+        if predicates.is_empty() {
+            break;
+        }
+        for (name, (table, predicates)) in predicates {
+            current.insert(name.clone(), push_cte(&mut ctes, &table, &predicates));
+            depths.insert(name.clone(), d);
+            predicates_of.insert(name, predicates);
+        }
+    }
+
+    let included = current
+        .values()
+        .map(|i| ctes[*i].table.clone())
+        .collect::<Vec<Table>>();
+    let included_relations = relations
+        .iter()
+        .filter(|r| depths.contains_key(&r.table.name) && depths.contains_key(&r.parent_table.name))
+        .cloned()
+        .collect::<Vec<Relation>>();
+    let (order, ignored) = Graph::new(&included, &included_relations).topological_order();
+
+    // Children come before their parents in the reverse order, so their rows are complete
+    let mut extended = BTreeSet::new();
+    for name in order.iter().rev() {
+        let mut predicates = predicates_of[name].clone();
+        let count = predicates.len();
+        for (i, r) in relations.iter().enumerate() {
+            if r.parent_table.name != *name
+                || !depths.contains_key(&r.table.name)
+                || (followed_to_children.contains(&i) && !extended.contains(&r.table.name))
+                || ignored.contains(&(r.table.name.clone(), r.parent_table.name.clone()))
+            {
+                continue;
+            }
+            let p = predicate(
+                &r.parent_columns,
+                &r.columns,
+                &ctes,
+                current[&r.table.name],
+                dialect,
+            );
+            // Parents found from the children are already selected unless the children are extended
+            if !predicates.contains(&p) {
+                predicates.push(p);
+            }
+        }
+        if predicates.len() > count {
+            let table = ctes[current[name]].table.clone();
+            current.insert(name.clone(), push_cte(&mut ctes, &table, &predicates));
+            extended.insert(name.clone());
+        }
+    }
+
+    Ok(order
+        .into_iter()
+        .map(|name| {
+            let index = current[&name];
+            SubsetTable {
+                table: ctes[index].table.clone(),
+                depth: depths[&name],
+                ctes: ctes[index]
+                    .dependencies
+                    .iter()
+                    .chain([&index])
+                    .map(|i| {
+                        let cte = &ctes[*i];
+                        (
+                            cte.name.clone(),
+                            format!(
+                                "SELECT * FROM {} WHERE {}",
+                                qualified_name(&cte.table, dialect),
+                                cte.condition
+                            ),
+                        )
+                    })
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
+/// Add an expression selecting the rows of the table that match any of the predicates
+fn push_cte(ctes: &mut Vec<Cte>, table: &Table, predicates: &[Predicate]) -> usize {
+    let dependencies = predicates
+        .iter()
+        .filter_map(|(_, nested)| *nested)
+        .flat_map(|i| ctes[i].dependencies.iter().copied().chain([i]))
+        .collect();
+    let index = ctes.len();
+    ctes.push(Cte {
+        // Table names are not used, since they may be too long for identifiers with the index
+        name: format!("s{}", index),
+        table: table.clone(),
+        condition: any(predicates),
+        dependencies,
+    });
+
+    index
+}
+
+/// Condition that the columns match the nested columns of the rows of the expression
+fn predicate(
+    columns: &[Column],
+    nested_columns: &[Column],
+    ctes: &[Cte],
+    nested: usize,
+    dialect: Dialect,
+) -> Predicate {
+    (
+        format!(
+            "{} IN (SELECT {} FROM {})",
+            tuple(columns.iter().map(|c| dialect.quote(&c.name))),
+            nested_columns
+                .iter()
+                .map(|c| dialect.quote(&c.name))
+                .join(", "),
+            dialect.quote(&ctes[nested].name)
+        ),
+        Some(nested),
+    )
+}
+
+fn any(predicates: &[Predicate]) -> String {
+    if predicates.len() == 1 {
+        predicates[0].0.clone()
+    } else {
+        predicates
+            .iter()
+            .map(|(p, _)| format!("({})", p))
+            .join(" OR ")
+    }
+}
+
+/// Expression rendering the value of the column as an SQL literal, which is `NULL` for null values
+/// Binary values are written as hexadecimal literals and the others are converted to utf8mb4, so
+/// that the literal can be read as a string whatever the data type is.
+pub(crate) fn literal(expression: &str, column: &Column) -> String {
+    let data_type = column.parsed_data_type();
+    let is_binary = data_type.family() == DataTypeFamily::Binary
+        || matches!(
+            data_type.base.as_str(),
+            "geometry"
+                | "point"
+                | "linestring"
+                | "polygon"
+                | "multipoint"
+                | "multilinestring"
+                | "multipolygon"
+                | "geometrycollection"
+                | "geomcollection"
+        );
+    if is_binary {
+        format!(
+            "IF({} IS NULL, 'NULL', CONCAT('X''', HEX({}), ''''))",
+            expression, expression
+        )
+    } else {
+        format!("QUOTE(CONVERT({} USING utf8mb4))", expression)
+    }
+}
+
+fn qualified_name(table: &Table, dialect: Dialect) -> String {
+    format!(
+        "{}.{}",
+        dialect.quote(&table.database),
+        dialect.quote(&table.name)
+    )
+}
+
+/// Columns as is, or a row constructor for composite columns
+fn tuple(columns: impl Iterator<Item = String>) -> String {
+    let columns = columns.collect::<Vec<String>>();
+    if columns.len() == 1 {
+        columns[0].clone()
+    } else {
+        format!("({})", columns.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::{relation, table};
+
+    fn tables() -> Vec<Table> {
+        vec![
+            table("users", &["id"]),
+            table("posts", &["id", "users_id"]),
+            table("comments", &["id", "posts_id"]),
+        ]
+    }
+
+    fn relations(tables: &[Table]) -> Vec<Relation> {
+        vec![
+            relation(tables, "posts", &["users_id"], "users", &["id"], true),
+            relation(tables, "comments", &["posts_id"], "posts", &["id"], true),
+        ]
+    }
+
+    fn names(subset: &[SubsetTable]) -> Vec<(&str, usize)> {
+        subset
+            .iter()
+            .map(|s| (s.table.name.as_str(), s.depth))
+            .collect()
+    }
+
+    #[test]
+    fn children_within_depth() {
+        let tables = tables();
+        let subset = subset(
+            &tables,
+            &relations(&tables),
+            "users",
+            "id = 1",
+            1,
+            Dialect::MySql,
+        )
+        .unwrap();
+        assert_eq!(names(&subset), vec![("users", 0), ("posts", 1)]);
+    }
+
+    #[test]
+    fn parents_beyond_depth() {
+        let tables = tables();
+        let subset = subset(
+            &tables,
+            &relations(&tables),
+            "comments",
+            "id = 1",
+            0,
+            Dialect::MySql,
+        )
+        .unwrap();
+        assert_eq!(
+            names(&subset),
+            vec![("users", 2), ("posts", 1), ("comments", 0)]
+        );
+        assert_eq!(
+            subset[0].select(Dialect::MySql),
+            "WITH\n  \
+             `s0` AS (SELECT * FROM `app`.`comments` WHERE id = 1),\n  \
+             `s1` AS (SELECT * FROM `app`.`posts` WHERE `id` IN (SELECT `posts_id` FROM `s0`)),\n  \
+             `s2` AS (SELECT * FROM `app`.`users` WHERE `id` IN (SELECT `users_id` FROM `s1`))\n\
+             SELECT * FROM `s2`;"
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod check;
+mod inference;
+
+pub(crate) use check::*;
+pub(crate) use inference::*;
@@ -0,0 +1,367 @@
+use crate::configs::Lint;
+use crate::databases::{glob_match, Graph, Relation, Table};
+use itertools::Itertools;
+use serde::Serialize;
+
+/// Violation of a lint rule
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Violation {
+    pub(crate) rule: String,
+    pub(crate) target: String,
+    pub(crate) message: String,
+}
+
+impl Violation {
+    fn new(rule: &str, target: String, message: String) -> Self {
+        Violation {
+            rule: rule.to_string(),
+            target,
+            message,
+        }
+    }
+}
+
+/// Evaluate the enabled lint rules against the tables and the relations
+/// `declared` holds the relations of each configuration file before deduplication, which
+/// duplicateRelations compares with the foreign keys and with the others of the same file.
+pub(crate) fn check_lint(
+    lint: &Lint,
+    tables: &[Table],
+    relations: &[Relation],
+    declared: &[Vec<Relation>],
+) -> Vec<Violation> {
+    let tables = tables
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect::<Vec<&Table>>();
+    let mut violations = Vec::new();
+
+    if let Some(rule) = lint.require_table_comment.as_ref().filter(|r| r.enabled) {
+        let targets = tables
+            .iter()
+            .filter(|t| !is_excluded(&rule.exclude, &t.name))
+            .collect::<Vec<_>>();
+        let uncommented = targets
+            .iter()
+            .filter(|t| t.comment.is_none())
+            .collect::<Vec<_>>();
+        if !(rule.all_or_nothing && uncommented.len() == targets.len()) {
+            for table in uncommented {
+                violations.push(Violation::new(
+                    "requireTableComment",
+                    table.name.clone(),
+                    format!("table `{}` has no comment", table.name),
+                ));
+            }
+        }
+    }
+
+    if let Some(rule) = lint.require_column_comment.as_ref().filter(|r| r.enabled) {
+        for table in tables
+            .iter()
+            .filter(|t| !is_excluded(&rule.exclude_tables, &t.name))
+        {
+            let targets = table
+                .columns
+                .iter()
+                .filter(|c| {
+                    !is_excluded(&rule.exclude, &c.name)
+                        && !is_excluded(&rule.exclude, &format!("{}.{}", table.name, c.name))
+                })
+                .collect::<Vec<_>>();
+            let uncommented = targets
+                .iter()
+                .filter(|c| c.comment.is_none())
+                .collect::<Vec<_>>();
+            if rule.all_or_nothing && uncommented.len() == targets.len() {
+                continue;
+            }
+            for column in uncommented {
+                violations.push(Violation::new(
+                    "requireColumnComment",
+                    format!("{}.{}", table.name, column.name),
+                    format!("column `{}.{}` has no comment", table.name, column.name),
+                ));
+            }
+        }
+    }
+
+    if let Some(rule) = lint.unrelated_table.as_ref().filter(|r| r.enabled) {
+        let graph = Graph::new(&[], relations);
+        let targets = tables
+            .iter()
+            .filter(|t| !is_excluded(&rule.exclude, &t.name))
+            .collect::<Vec<_>>();
+        let unrelated = targets
+            .iter()
+            .filter(|t| !graph.nodes.contains(&t.name))
+            .collect::<Vec<_>>();
+        if !(rule.all_or_nothing && unrelated.len() == targets.len()) {
+            for table in unrelated {
+                violations.push(Violation::new(
+                    "unrelatedTable",
+                    table.name.clone(),
+                    format!("table `{}` has no relations", table.name),
+                ));
+            }
+        }
+    }
+
+    if let Some(rule) = lint.column_count.as_ref().filter(|r| r.enabled) {
+        for table in tables
+            .iter()
+            .filter(|t| !is_excluded(&rule.exclude, &t.name))
+            .filter(|t| t.columns.len() as i32 > rule.max)
+        {
+            violations.push(Violation::new(
+                "columnCount",
+                table.name.clone(),
+                format!(
+                    "table `{}` has too many columns ({} > {})",
+                    table.name,
+                    table.columns.len(),
+                    rule.max
+                ),
+            ));
+        }
+    }
+
+    if let Some(rule) = lint.require_columns.as_ref().filter(|r| r.enabled) {
+        for required in rule.columns.iter() {
+            for table in tables
+                .iter()
+                .filter(|t| !is_excluded(&required.exclude, &t.name))
+                .filter(|t| !t.columns.iter().any(|c| c.name == required.name))
+            {
+                violations.push(Violation::new(
+                    "requireColumns",
+                    table.name.clone(),
+                    format!("table `{}` has no column `{}`", table.name, required.name),
+                ));
+            }
+        }
+    }
+
+    if lint.duplicate_relations.as_ref().is_some_and(|r| r.enabled) {
+        violations.extend(duplicate_relations(relations, declared));
+    }
+
+    if let Some(rule) = lint
+        .require_foreign_key_index
+        .as_ref()
+        .filter(|r| r.enabled)
+    {
+        for r in relations
+            .iter()
+            .filter(|r| !r.table.has_index_on(&r.columns))
+            .filter(|r| {
+                !is_excluded(&rule.exclude, &r.table.name)
+                    && !r.columns.iter().any(|c| {
+                        is_excluded(&rule.exclude, &format!("{}.{}", r.table.name, c.name))
+                    })
+            })
+        {
+            violations.push(Violation::new(
+                "requireForeignKeyIndex",
+                format!(
+                    "{}.{}",
+                    r.table.name,
+                    r.columns.iter().map(|c| &c.name).join(",")
+                ),
+                format!(
+                    "relation `{}` has no index on the child columns",
+                    r.definition()
+                ),
+            ));
+        }
+    }
+
+    violations
+}
+
+/// Relations of each configuration file that duplicate a foreign key or another relation of the file
+/// Detected relations are never duplicates, since they are dropped in favor of the others.
+pub(crate) fn duplicate_relations(
+    relations: &[Relation],
+    declared: &[Vec<Relation>],
+) -> Vec<Violation> {
+    let foreign_keys = relations
+        .iter()
+        .filter(|r| !r.is_virtual)
+        .collect::<Vec<_>>();
+    let mut violations = Vec::new();
+    for relations in declared.iter() {
+        for (i, r) in relations.iter().enumerate() {
+            let message = if foreign_keys.iter().any(|fk| fk.is_same(r)) {
+                format!("relation `{}` duplicates a foreign key", r.definition())
+            } else if relations[..i].iter().any(|other| other.is_same(r)) {
+                format!("relation `{}` is duplicated", r.definition())
+            } else {
+                continue;
+            };
+            violations.push(Violation::new(
+                "duplicateRelations",
+                r.table.name.clone(),
+                message,
+            ));
+        }
+    }
+
+    violations
+}
+
+fn is_excluded(exclude: &[String], name: &str) -> bool {
+    exclude.iter().any(|pattern| glob_match(pattern, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::configs::{
+        ColumnCount, DuplicateRelations, RequireColumns, RequireColumnsColumn,
+        RequireForeignKeyIndex, RequireTableComment, UnrelatedTable,
+    };
+    use crate::databases::testing::{relation, table, with_foreign_key};
+
+    fn strings(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn messages(violations: Vec<Violation>) -> Vec<String> {
+        violations.into_iter().map(|v| v.message).collect()
+    }
+
+    fn tables() -> Vec<Table> {
+        let mut users = table("users", &["id", "name"]);
+        users.comment = Some("Users".to_string());
+        vec![
+            users,
+            table("posts", &["id", "user_id"]),
+            table("logs", &["id", "message", "level"]),
+        ]
+    }
+
+    #[test]
+    fn require_table_comment() {
+        let tables = tables();
+        let lint = |exclude: &[&str], all_or_nothing: bool| Lint {
+            require_table_comment: Some(RequireTableComment {
+                enabled: true,
+                all_or_nothing,
+                exclude: strings(exclude),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(check_lint(&lint(&["l*"], false), &tables, &[], &[])),
+            strings(&["table `posts` has no comment"])
+        );
+        // Tables without any comments are allowed when all of them have none
+        assert!(check_lint(&lint(&["users"], true), &tables, &[], &[]).is_empty());
+        assert_eq!(check_lint(&lint(&[], true), &tables, &[], &[]).len(), 2);
+    }
+
+    #[test]
+    fn unrelated_table_and_foreign_key_index() {
+        let tables = tables();
+        let relations = vec![relation(
+            &tables,
+            "posts",
+            &["user_id"],
+            "users",
+            &["id"],
+            true,
+        )];
+        let lint = |exclude: &[&str]| Lint {
+            unrelated_table: Some(UnrelatedTable {
+                enabled: true,
+                exclude: strings(exclude),
+                ..Default::default()
+            }),
+            require_foreign_key_index: Some(RequireForeignKeyIndex {
+                enabled: true,
+                exclude: strings(exclude),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(check_lint(&lint(&[]), &tables, &relations, &[])),
+            strings(&[
+                "table `logs` has no relations",
+                "relation `posts(user_id) -> users(id)` has no index on the child columns",
+            ])
+        );
+        assert!(check_lint(&lint(&["logs", "posts.user_id"]), &tables, &relations, &[]).is_empty());
+    }
+
+    #[test]
+    fn column_count_and_require_columns() {
+        let tables = tables();
+        let lint = Lint {
+            column_count: Some(ColumnCount {
+                enabled: true,
+                max: 2,
+                ..Default::default()
+            }),
+            require_columns: Some(RequireColumns {
+                enabled: true,
+                columns: vec![RequireColumnsColumn {
+                    name: "name".to_string(),
+                    exclude: strings(&["logs"]),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(check_lint(&lint, &tables, &[], &[])),
+            strings(&[
+                "table `logs` has too many columns (3 > 2)",
+                "table `posts` has no column `name`",
+            ])
+        );
+    }
+
+    #[test]
+    fn duplicate_relations_of_each_file() {
+        let tables = vec![
+            table("users", &["id"]),
+            with_foreign_key(
+                table("posts", &["id", "user_id", "editor_id"]),
+                &["user_id"],
+                "users",
+                &["id"],
+            ),
+        ];
+        let foreign_key = relation(&tables, "posts", &["user_id"], "users", &["id"], false);
+        let editor = relation(&tables, "posts", &["editor_id"], "users", &["id"], true);
+        let forced = vec![
+            relation(&tables, "posts", &["user_id"], "users", &["id"], true),
+            editor.clone(),
+            editor.clone(),
+        ];
+        let configured = vec![editor.clone()];
+        let lint = Lint {
+            duplicate_relations: Some(DuplicateRelations {
+                enabled: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            messages(check_lint(
+                &lint,
+                &tables,
+                &[foreign_key, editor],
+                &[forced, configured]
+            )),
+            strings(&[
+                "relation `posts(user_id) -> users(id)` duplicates a foreign key",
+                "relation `posts(editor_id) -> users(id)` is duplicated",
+            ])
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::configs::{
+    ColumnCount, DuplicateRelations, Lint, RequireColumnComment, RequireColumns,
+    RequireColumnsColumn, RequireForeignKeyIndex, RequireTableComment, UnrelatedTable,
+};
+use crate::databases::{Graph, Relation, Table};
+use crate::lints::duplicate_relations;
+use itertools::Itertools;
+
+/// Ratio of tables a column must be present in to be required in all tables
+const REQUIRE_COLUMNS_RATIO: f64 = 0.9;
+
+/// Percentile of the column counts used as the maximum column count
+const COLUMN_COUNT_PERCENTILE: f64 = 0.95;
+
+/// Infer lint settings from the conventions observed in the tables and the relations
+/// Rules are configured so that the current schema passes them, with deviating tables excluded.
+/// `declared` holds the relations of each configuration file as in `check_lint`.
+pub(crate) fn infer_lint(
+    tables: &[Table],
+    relations: &[Relation],
+    declared: &[Vec<Relation>],
+) -> Lint {
+    let tables = tables
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect::<Vec<&Table>>();
+
+    Lint {
+        require_table_comment: Some(RequireTableComment {
+            enabled: !tables.is_empty() && tables.iter().all(|t| t.comment.is_some()),
+            ..Default::default()
+        }),
+        require_column_comment: Some(RequireColumnComment {
+            enabled: !tables.is_empty()
+                && tables
+                    .iter()
+                    .all(|t| t.columns.iter().all(|c| c.comment.is_some())),
+            ..Default::default()
+        }),
+        unrelated_table: Some(infer_unrelated_table(&tables, relations)),
+        column_count: Some(infer_column_count(&tables)),
+        require_columns: Some(infer_require_columns(&tables)),
+        duplicate_relations: Some(DuplicateRelations {
+            enabled: duplicate_relations(relations, declared).is_empty(),
+            ..Default::default()
+        }),
+        require_foreign_key_index: Some(infer_require_foreign_key_index(relations)),
+        ..Default::default()
+    }
+}
+
+fn infer_unrelated_table(tables: &[&Table], relations: &[Relation]) -> UnrelatedTable {
+    let graph = Graph::new(&[], relations);
+    UnrelatedTable {
+        enabled: true,
+        all_or_nothing: false,
+        exclude: tables
+            .iter()
+            .filter(|t| !graph.nodes.contains(&t.name))
+            .map(|t| t.name.clone())
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn infer_column_count(tables: &[&Table]) -> ColumnCount {
+    let counts = tables
+        .iter()
+        .map(|t| t.columns.len())
+        .sorted()
+        .collect::<Vec<usize>>();
+    if counts.is_empty() {
+        return ColumnCount::default();
+    }
+    let index = ((counts.len() as f64 * COLUMN_COUNT_PERCENTILE).ceil() as usize).max(1) - 1;
+    let max = counts[index];
+
+    ColumnCount {
+        enabled: true,
+        max: max as i32,
+        exclude: tables
+            .iter()
+            .filter(|t| t.columns.len() > max)
+            .map(|t| t.name.clone())
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn infer_require_columns(tables: &[&Table]) -> RequireColumns {
+    let columns = tables
+        .iter()
+        .flat_map(|t| t.columns.iter().map(|c| &c.name).unique())
+        .counts()
+        .into_iter()
+        .filter(|(_, count)| {
+            tables.len() > 1 && *count as f64 > tables.len() as f64 * REQUIRE_COLUMNS_RATIO
+        })
+        .map(|(name, _)| RequireColumnsColumn {
+            name: name.clone(),
+            exclude: tables
+                .iter()
+                .filter(|t| !t.columns.iter().any(|c| &c.name == name))
+                .map(|t| t.name.clone())
+                .collect(),
+            ..Default::default()
+        })
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .collect::<Vec<RequireColumnsColumn>>();
+
+    RequireColumns {
+        enabled: !columns.is_empty(),
+        columns,
+        ..Default::default()
+    }
+}
+
+fn infer_require_foreign_key_index(relations: &[Relation]) -> RequireForeignKeyIndex {
+    RequireForeignKeyIndex {
+        enabled: true,
+        exclude: relations
+            .iter()
+            .filter(|r| !r.table.has_index_on(&r.columns))
+            .flat_map(|r| {
+                r.columns
+                    .iter()
+                    .map(|c| format!("{}.{}", r.table.name, c.name))
+            })
+            .unique()
+            .sorted()
+            .collect(),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::databases::testing::{relation, table, with_foreign_key};
+    use crate::databases::{declared_relations, dedup_relations};
+
+    fn tables() -> Vec<Table> {
+        vec![
+            table("users", &["id"]),
+            with_foreign_key(
+                table("posts", &["id", "users_id"]),
+                &["users_id"],
+                "users",
+                &["id"],
+            ),
+        ]
+    }
+
+    fn is_duplicate_relations_enabled(declared: &[Vec<Relation>]) -> bool {
+        let tables = tables();
+        let relations = dedup_relations(
+            declared_relations(&tables)
+                .into_iter()
+                .chain(declared.iter().flatten().cloned())
+                .collect(),
+        );
+        infer_lint(&tables, &relations, declared)
+            .duplicate_relations
+            .unwrap()
+            .enabled
+    }
+
+    #[test]
+    fn duplicate_relations_enabled_without_duplicates() {
+        assert!(is_duplicate_relations_enabled(&[Vec::new(), Vec::new()]));
+    }
+
+    #[test]
+    fn duplicate_relations_disabled_with_forced_foreign_key() {
+        let tables = tables();
+        let forced = vec![relation(
+            &tables,
+            "posts",
+            &["users_id"],
+            "users",
+            &["id"],
+            true,
+        )];
+        assert!(!is_duplicate_relations_enabled(&[forced, Vec::new()]));
+    }
+}
@@ -1,8 +1,13 @@
 mod commands;
 mod configs;
 mod databases;
+mod generators;
+mod lints;
 
-use crate::commands::{run_generate, Cli, Commands};
+use crate::commands::{
+    run_analyze, run_generate, run_impact, run_lint, run_order, run_report, run_subset,
+    run_validate, Cli, Commands,
+};
 use anyhow::Result;
 use clap::Parser;
 use std::io::Write;
@@ -16,11 +21,18 @@ async fn main() -> Result<()> {
         .filter_level(if args.verbose {
             log::LevelFilter::Debug
         } else {
-            log::LevelFilter::Error
+            log::LevelFilter::Warn
         })
         .init();
 
     match args.command {
         Commands::Generate { command } => run_generate(command).await,
+        Commands::Lint { args } => run_lint(args).await,
+        Commands::Validate { args } => run_validate(args).await,
+        Commands::Analyze { args } => run_analyze(args).await,
+        Commands::Order { args } => run_order(args).await,
+        Commands::Subset { args } => run_subset(args).await,
+        Commands::Impact { args } => run_impact(args).await,
+        Commands::Report { command } => run_report(command).await,
     }
 }